@@ -1,6 +1,7 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{panic_with_error, Address, Env};
 
-use crate::storage_types::DataKey;
+use crate::error::ContractError;
+use crate::storage_types::{DataKey, PendingAdmin};
 
 pub fn has_administrator(e: &Env) -> bool {
     let key = DataKey::Admin;
@@ -9,10 +10,28 @@ pub fn has_administrator(e: &Env) -> bool {
 
 pub fn read_administrator(e: &Env) -> Address {
     let key = DataKey::Admin;
-    e.storage().instance().get(&key).unwrap()
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(e, ContractError::NotInitialized))
 }
 
 pub fn write_administrator(e: &Env, id: &Address) {
     let key = DataKey::Admin;
     e.storage().instance().set(&key, id);
-}
\ No newline at end of file
+}
+
+pub fn read_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_pending_admin(e: &Env, pending: &PendingAdmin) {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().set(&key, pending);
+}
+
+pub fn remove_pending_admin(e: &Env) {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().remove(&key);
+}
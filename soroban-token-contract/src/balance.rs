@@ -1,5 +1,6 @@
+use crate::error::ContractError;
 use crate::storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{panic_with_error, Address, Env};
 
 pub fn read_balance(e: &Env, addr: Address) -> i128 {
     let key = DataKey::Balance(addr);
@@ -29,7 +30,7 @@ pub fn receive_balance(e: &Env, addr: Address, amount: i128) {
 pub fn spend_balance(e: &Env, addr: Address, amount: i128) {
     let balance = read_balance(e, addr.clone());
     if balance < amount {
-        panic!("insufficient balance");
+        panic_with_error!(e, ContractError::InsufficientBalance);
     }
     write_balance(e, addr, balance - amount);
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+use soroban_sdk::Env;
+
+use crate::storage_types::DataKey;
+
+pub fn read_clawback_enabled(e: &Env) -> bool {
+    let key = DataKey::ClawbackEnabled;
+    e.storage().instance().get(&key).unwrap_or(false)
+}
+
+pub fn write_clawback_enabled(e: &Env, enabled: bool) {
+    let key = DataKey::ClawbackEnabled;
+    e.storage().instance().set(&key, &enabled);
+}
@@ -1,97 +1,386 @@
-use crate::admin::{has_administrator, read_administrator, write_administrator};
+use crate::admin::{
+    has_administrator, read_administrator, read_pending_admin, remove_pending_admin,
+    write_administrator, write_pending_admin,
+};
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
 use crate::balance::{read_balance, receive_balance, spend_balance};
+use crate::clawback::{read_clawback_enabled, write_clawback_enabled};
+use crate::council::{
+    next_council_proposal_id, read_council, read_council_proposal, remove_council,
+    remove_council_proposal, write_council, write_council_proposal,
+};
+use crate::error::{ContractError, MarketError, SchemaError, SupplyError};
+use crate::freeze::{read_freeze_status, remove_freeze_status, write_freeze_status};
+use crate::interest::{self, accrue_index};
+use crate::lending::{
+    has_market, migrate_legacy_pool_to_market, migrate_market_reserves, read_interest_rate_model,
+    read_market, read_market_swept, read_markets, read_total_collateral, read_use_as_collateral,
+    read_user_borrow, read_user_supply, remove_user_borrow, remove_user_supply,
+    write_interest_rate_model, write_market, write_market_swept, write_total_collateral,
+    write_use_as_collateral, write_user_borrow, write_user_supply,
+};
+use crate::mint_limit::{read_mint_history, read_mint_limit, write_mint_history, write_mint_limit};
+use crate::oracle::{read_oracle_config, write_oracle_config, Asset, PriceOracleClient};
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
-use crate::storage_types::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
-use crate::storage_types::{DataKey, VestingSchedule,StakeInfo,PoolInfo};
+use crate::pause::{read_paused, write_paused};
+use crate::role::{has_role, remove_role, transfer_roles, write_role, ALL_ROLES};
+use crate::schema::{read_schema_version, write_schema_version};
+use crate::timelock::{next_operation_id, read_queued_operations, write_queued_operations};
+use crate::staking::{has_stake_info, read_stake_info, remove_stake_info, write_stake_info};
+use crate::vesting::{
+    add_vesting_schedule, migrate_legacy_vesting_schedule, migrate_vesting_funders,
+    migrate_vesting_to_persistent,
+    next_vesting_id, read_vesting_escrow, read_vesting_ids, read_vesting_schedule,
+    remove_vesting_schedule, write_vesting_escrow, write_vesting_schedule,
+};
+use crate::supply::{
+    migrate_total_supply, read_max_supply, read_seeded_total_supply, read_total_supply,
+    write_max_supply, write_total_supply,
+};
+use crate::storage_types::{CURRENT_SCHEMA_VERSION, INDEX_PRECISION, MINT_WINDOW_BUCKETS, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, LEGACY_REWARD_DURATION, REWARD_PRECISION, TIMELOCK_DELAY};
+use crate::storage_types::{Council, CouncilAction, CouncilProposal, DataKey, FreezeMode, FreezeStatus, InterestRateModel, MintLimit, MintRecord, OracleConfig, PauseOperation, PendingAdmin, QueuedOperation, Role, TimelockOperation, VestingParams, VestingSchedule,StakeInfo,LegacyStakeInfo,LegacyPoolInfo,LegacyRewardTerms,PoolInfo,LendingPool,UserSupply,UserBorrow};
 use soroban_sdk::token::{self, Interface as _};
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
-use soroban_sdk::{contract, contractimpl, Address, Env, String, Map};
+use soroban_sdk::{contract, contractimpl, panic_with_error, Address, BytesN, Env, String, Map, Symbol, TryFromVal, Val, Vec};
 
 
-///staking kodları
-
-// Kontrat veri yapısı
-#[contract]
-pub struct StakingRewards;
+// Staking kodları
 
 // Verileri saklamak için kullanılacak anahtarlar
 const POOL_INFO_KEY: &str = "pool_info";
-const STAKES_KEY: &str = "stakes";
-const ADMIN_KEY: &str = "admin";
-const LENDING_POOL_KEY: &str = "lending_pool";
+// Rollerden önceki sürümün staking admin anahtarı, şema 1'e geçişte silinir
+const LEGACY_ADMIN_KEY: &str = "admin";
+// Eski sürümün stake haritası, sadece migrate_stakes tarafından okunur
+pub(crate) const STAKES_KEY: &str = "stakes";
+
+// create_vesting_batch ile tek çağrıda oluşturulabilecek en fazla plan sayısı.
+// Bütçe testlerinde 50 plan yaklaşık 9M instruction ve 8.4 KiB olay üretirken
+// 100 plan 16 KiB olay sınırını aşıyor. vesting_batch_fits_in_budget testi bu
+// boyuttaki bir batch'in ağ sınırlarının altında kaldığını doğrular.
+const MAX_VESTING_BATCH_SIZE: u32 = 50;
 
 // Özel olayları yayınlamak için yardımcı fonksiyon
 fn emit_event(e: &Env, event_type: &str, user: &Address, amount: i128) {
     e.events().publish((event_type, user.clone(), amount), ());
 }
+
+// Lending olaylarında hangi piyasanın etkilendiği de yayınlanır
+fn emit_market_event(e: &Env, event_type: &str, asset: &Address, user: &Address, amount: i128) {
+    e.events().publish((event_type, asset.clone(), user.clone()), amount);
+}
 //////
 
 
-fn check_nonnegative_amount(amount: i128) {
+fn check_nonnegative_amount(e: &Env, amount: i128) {
     if amount < 0 {
-        panic!("negative amount is not allowed: {}", amount)
+        panic_with_error!(e, ContractError::NegativeAmount)
     }
 }
 
-// Bir hesabın dondurulup dondurulmadığını kontrol eden yardımcı fonksiyon
-fn is_account_frozen(e: &Env, account: &Address) -> bool {
-    let key = DataKey::Frozen(account.clone());
-    e.storage().instance().get::<_, bool>(&key).unwrap_or(false)
+// Piyasa oranları MAX_RATE_BPS'i aşamaz
+fn check_lending_rates(e: &Env, supply_rate: u32, borrow_rate: u32) {
+    if supply_rate > interest::MAX_RATE_BPS || borrow_rate > interest::MAX_RATE_BPS {
+        panic_with_error!(e, MarketError::InvalidLendingRates);
+    }
 }
 
-// Özel olayları yayınlamak için yardımcı fonksiyon
-fn emit_custom_event(e: &Env, event_type: &str, admin: Address, account: Address) {
-    e.events().publish((event_type, admin, account), ());
+// Staking havuzlarının ve lending piyasalarının token hareketleri için yardımcı fonksiyon. Kontratın kendi tokeni
+// kendi kendini çağıramayacağı için bakiyeler doğrudan güncellenir, diğer SEP-41
+// tokenleri için token::Client kullanılır.
+fn transfer_pool_token(e: &Env, token_id: &Address, from: &Address, to: &Address, amount: i128) {
+    if *token_id == e.current_contract_address() {
+        spend_balance(e, from.clone(), amount);
+        receive_balance(e, to.clone(), amount);
+    } else {
+        token::Client::new(e, token_id).transfer(from, to, &amount);
+    }
 }
 
-// Vesting planı işlemleri için yardımcı fonksiyonlar
-fn read_vesting_schedule(e: &Env, beneficiary: &Address) -> Option<VestingSchedule> {
-    let key = DataKey::VestingSchedule(beneficiary.clone());
-    e.storage().instance().get(&key)
+fn token_balance(e: &Env, token_id: &Address, id: &Address) -> i128 {
+    if *token_id == e.current_contract_address() {
+        read_balance(e, id.clone())
+    } else {
+        token::Client::new(e, token_id).balance(id)
+    }
 }
 
-fn write_vesting_schedule(e: &Env, beneficiary: &Address, schedule: &VestingSchedule) {
-    let key = DataKey::VestingSchedule(beneficiary.clone());
-    e.storage().instance().set(&key, schedule);
+fn pool_token_balance(e: &Env, token_id: &Address) -> i128 {
+    token_balance(e, token_id, &e.current_contract_address())
 }
 
-fn remove_vesting_schedule(e: &Env, beneficiary: &Address) {
-    let key = DataKey::VestingSchedule(beneficiary.clone());
-    e.storage().instance().remove(&key);
+// Hesaptan token çıkışı dondurulmuşsa işlemi reddeden yardımcı fonksiyon
+fn check_can_send(e: &Env, account: &Address) {
+    if let Some(status) = read_freeze_status(e, account) {
+        if status.mode != FreezeMode::Incoming {
+            panic_with_error!(e, ContractError::AccountFrozen);
+        }
+    }
 }
 
-fn get_claimable_amount(e: &Env, beneficiary: &Address) -> i128 {
-    if let Some(schedule) = read_vesting_schedule(e, beneficiary) {
-        let current_ledger = e.ledger().sequence();
-        
-        // Eğer henüz başlangıç zamanına gelmemişse veya cliff zamanından önceyse
-        if current_ledger < schedule.start_ledger || 
-           (schedule.cliff_ledger > 0 && current_ledger < schedule.cliff_ledger) {
-            return 0;
+// Hesaba token girişi dondurulmuşsa işlemi reddeden yardımcı fonksiyon
+fn check_can_receive(e: &Env, account: &Address) {
+    if !can_receive(e, account) {
+        panic_with_error!(e, ContractError::AccountFrozen);
+    }
+}
+
+fn can_receive(e: &Env, account: &Address) -> bool {
+    read_freeze_status(e, account).is_none_or(|status| status.mode == FreezeMode::Outgoing)
+}
+
+// Oracle'dan bir tokenin fiyatını okuyan ve tazelik ile sapma kontrollerini yapan yardımcı fonksiyon
+fn oracle_price(e: &Env, config: &OracleConfig, asset: &Address) -> i128 {
+    let client = PriceOracleClient::new(e, &config.oracle);
+    let oracle_asset = Asset::Stellar(asset.clone());
+    let last = client
+        .lastprice(&oracle_asset)
+        .unwrap_or_else(|| panic_with_error!(e, MarketError::PriceUnavailable));
+    if last.price <= 0 {
+        panic_with_error!(e, MarketError::PriceUnavailable);
+    }
+    if e.ledger().timestamp().saturating_sub(last.timestamp) > config.max_age {
+        panic_with_error!(e, MarketError::StalePrice);
+    }
+
+    // Son fiyat bir önceki turdan çok saparsa manipülasyon ihtimaline karşı reddedilir
+    if let Some(previous) = client.prices(&oracle_asset, &2).and_then(|prices| prices.get(1)) {
+        if previous.price > 0 {
+            let deviation = (last.price - previous.price).abs() * 10000 / previous.price;
+            if deviation > config.max_deviation_bps as i128 {
+                panic_with_error!(e, MarketError::PriceDeviationTooHigh);
+            }
         }
-        
-        // Eğer bitiş zamanını geçtiyse, kalan tüm miktar çekilebilir
-        if current_ledger >= schedule.end_ledger {
-            return schedule.total_amount - schedule.claimed_amount;
+    }
+    last.price
+}
+
+// Tokenin bir tam biriminin taban birim karşılığı
+fn token_unit(e: &Env, asset: &Address) -> i128 {
+    let decimals = if *asset == e.current_contract_address() {
+        read_decimal(e)
+    } else {
+        token::Client::new(e, asset).decimals()
+    };
+    10i128.pow(decimals)
+}
+
+// Token miktarının oracle'ın kote para birimindeki değeri. Oracle tanımlı değilse sadece
+// kontratın kendi tokeni teminatla aynı birimde olduğu için ham miktarıyla değerlenebilir.
+fn asset_value(e: &Env, asset: &Address, amount: i128) -> i128 {
+    match read_oracle_config(e) {
+        Some(config) => amount * oracle_price(e, &config, asset) / token_unit(e, asset),
+        None if *asset == e.current_contract_address() => amount,
+        None => panic_with_error!(e, MarketError::OracleNotConfigured),
+    }
+}
+
+// Kote para birimindeki değerin token miktarı karşılığı (aşağı yuvarlanır)
+fn asset_amount(e: &Env, asset: &Address, value: i128) -> i128 {
+    match read_oracle_config(e) {
+        Some(config) => value * token_unit(e, asset) / oracle_price(e, &config, asset),
+        None if *asset == e.current_contract_address() => value,
+        None => panic_with_error!(e, MarketError::OracleNotConfigured),
+    }
+}
+
+// Yakılan miktarı toplam arzdan düşen yardımcı fonksiyon. Kayıtlı arzdan fazlası
+// yakılamaz; arz kaydı olmayan eski kurulumlarda yakma migrate'e kadar reddedilir.
+fn reduce_total_supply(e: &Env, amount: i128) {
+    let total_supply = read_seeded_total_supply(e)
+        .checked_sub(amount)
+        .filter(|total_supply| *total_supply >= 0)
+        .unwrap_or_else(|| panic_with_error!(e, SupplyError::SupplyUnderflow));
+    write_total_supply(e, total_supply);
+}
+
+// Bir işlem grubu durdurulmuşsa işlemi reddeden yardımcı fonksiyon
+fn check_not_paused(e: &Env, operation: PauseOperation) {
+    if read_paused(e, operation) {
+        panic_with_error!(e, ContractError::OperationPaused);
+    }
+}
+
+// Çağıranın gereken role sahip olmadığı durumda işlemi reddeden yardımcı fonksiyon
+fn check_role(e: &Env, role: Role, account: &Address) {
+    if !has_role(e, role, account) {
+        panic_with_error!(e, ContractError::MissingRole);
+    }
+}
+
+// Timelock işlemini sıraya alma ve iptal etme yetkisini kontrol eden yardımcı fonksiyon
+fn check_timelock_access(e: &Env, caller: &Address, operation: &TimelockOperation) {
+    match operation {
+        TimelockOperation::UpdateLendingRates(..)
+        | TimelockOperation::UpdateInterestRateModel(..)
+        | TimelockOperation::UpdateOracle(..)
+        | TimelockOperation::UpdateCollateralFactor(..)
+        | TimelockOperation::UpdateLiquidationParams(..) => check_role(e, Role::RiskManager, caller),
+        TimelockOperation::UpdateRewardDuration(..) => check_role(e, Role::StakingManager, caller),
+        TimelockOperation::ProposeAdmin(..)
+        | TimelockOperation::UpdateMaxSupply(..)
+        | TimelockOperation::UpdateMintLimit(..)
+        | TimelockOperation::RemoveMintLimit(..)
+        | TimelockOperation::Upgrade(..) => {
+            if *caller != read_administrator(e) {
+                panic_with_error!(e, ContractError::NotAdmin);
+            }
         }
-        
-        // Lineer vesting: Geçen zamana orantılı olarak token miktarı hesaplanır
-        let total_vesting_time = schedule.end_ledger - schedule.start_ledger;
-        let elapsed_time = current_ledger - schedule.start_ledger;
-        
-        let claimable_amount = schedule.total_amount * elapsed_time as i128 / total_vesting_time as i128;
-        
-        // Şimdiye kadar çekilen miktarı çıkaralım
-        if claimable_amount <= schedule.claimed_amount {
-            return 0;
+    }
+}
+
+// Mint geçmişi pencereyi MINT_WINDOW_BUCKETS eşit kovaya bölerek tutulur
+fn mint_bucket_ledgers(limit: &MintLimit) -> u32 {
+    limit.window_ledgers.div_ceil(MINT_WINDOW_BUCKETS)
+}
+
+// Minter'ın penceresi içinde kalan mint kovalarını ve bunların toplamını döndüren yardımcı
+// fonksiyon. Pencereye kısmen giren kova tamamen sayılır, böylece sınır hiç aşılmaz.
+fn mint_history_in_window(e: &Env, minter: &Address, limit: &MintLimit) -> (Vec<MintRecord>, i128) {
+    let ledger = e.ledger().sequence() as u64;
+    let span = mint_bucket_ledgers(limit) as u64 + limit.window_ledgers as u64;
+    let mut history = Vec::new(e);
+    let mut minted: i128 = 0;
+    for record in read_mint_history(e, minter).iter() {
+        if record.ledger as u64 + span > ledger {
+            minted += record.amount;
+            history.push_back(record);
         }
-        
-        return claimable_amount - schedule.claimed_amount;
+    }
+    (history, minted)
+}
+
+// Admin'in imzasını isteyen yardımcı fonksiyon. Konsey kurulduğunda admin kontratın
+// kendisi olur; admin işlemleri o andan sonra sadece konsey teklifleriyle yapılabilir.
+fn require_admin(e: &Env) -> Address {
+    let admin = read_administrator(e);
+    if admin == e.current_contract_address() {
+        panic_with_error!(e, ContractError::NotAdmin);
+    }
+    admin.require_auth();
+    admin
+}
+
+// Konsey üyelerinin ve onay eşiğinin geçerli olduğunu kontrol eden yardımcı fonksiyon
+fn validate_council(e: &Env, signers: &Vec<Address>, threshold: u32) {
+    if threshold == 0 || threshold > signers.len() {
+        panic_with_error!(e, ContractError::InvalidCouncil);
+    }
+    for (index, signer) in signers.iter().enumerate() {
+        if signers.last_index_of(&signer) != Some(index as u32) {
+            panic_with_error!(e, ContractError::InvalidCouncil);
+        }
+    }
+}
+
+// Konseyi okuyup çağıranın imzacı olduğunu kontrol eden yardımcı fonksiyon
+fn read_council_for_signer(e: &Env, signer: &Address) -> Council {
+    let council = read_council(e)
+        .unwrap_or_else(|| panic_with_error!(e, ContractError::CouncilNotInitialized));
+    if !council.signers.contains(signer) {
+        panic_with_error!(e, ContractError::NotCouncilSigner);
+    }
+    council
+}
+
+// Özel olayları yayınlamak için yardımcı fonksiyon
+fn emit_custom_event(e: &Env, event_type: &str, admin: Address, account: Address) {
+    e.events().publish((event_type, admin, account), ());
+}
+
+// Bir vesting planının parametrelerinin mantıklı olduğunu kontrol eden yardımcı fonksiyon
+fn validate_vesting_params(e: &Env, params: &VestingParams) {
+    check_nonnegative_amount(e, params.amount);
+    
+    if params.end_ledger <= params.start_ledger {
+        panic_with_error!(e, ContractError::InvalidVestingPeriod);
+    }
+    
+    // Dönem adımı vesting süresini aşamaz
+    if params.period_ledgers > params.end_ledger - params.start_ledger {
+        panic_with_error!(e, ContractError::InvalidVestingPeriod);
+    }
+    
+    if params.cliff_ledger > 0 && params.cliff_ledger < params.start_ledger {
+        panic_with_error!(e, ContractError::InvalidCliff);
     }
     
-    0 // Vesting planı yoksa çekilebilir miktar 0
+    if params.initial_unlock_percent > 100 {
+        panic_with_error!(e, ContractError::InvalidUnlockPercent);
+    }
+}
+
+// Emanete alınmış token'lar için vesting planını yeni bir id ile kaydeden yardımcı fonksiyon
+fn store_vesting_schedule(e: &Env, funder: &Address, params: VestingParams) -> u64 {
+    let schedule = VestingSchedule {
+        id: next_vesting_id(e),
+        beneficiary: params.beneficiary,
+        total_amount: params.amount,
+        claimed_amount: 0,
+        start_ledger: params.start_ledger,
+        cliff_ledger: params.cliff_ledger,
+        end_ledger: params.end_ledger,
+        period_ledgers: params.period_ledgers,
+        initial_unlock_percent: params.initial_unlock_percent,
+        revocable: params.revocable,
+        funder: Some(funder.clone()),
+    };
+    
+    add_vesting_schedule(e, &schedule);
+    
+    // Vesting oluşturma olayını yayınla
+    e.events().publish(
+        ("create_vesting", funder.clone(), schedule.beneficiary.clone()),
+        schedule.id,
+    );
+    
+    schedule.id
+}
+
+// Bir vesting planında şu ana kadar hak edilmiş toplam miktarı hesaplayan yardımcı fonksiyon
+fn get_vested_amount(e: &Env, schedule: &VestingSchedule) -> i128 {
+    let current_ledger = e.ledger().sequence();
+    
+    // Eğer henüz başlangıç zamanına gelmemişse veya cliff zamanından önceyse
+    if current_ledger < schedule.start_ledger || 
+       (schedule.cliff_ledger > 0 && current_ledger < schedule.cliff_ledger) {
+        return 0;
+    }
+    
+    // Eğer bitiş zamanını geçtiyse, tüm miktar hak edilmiştir
+    if current_ledger >= schedule.end_ledger {
+        return schedule.total_amount;
+    }
+    
+    // Cliff anında açılan ilk kısım, kalan miktar plana göre hak edilir
+    let initial_unlock = schedule.total_amount * schedule.initial_unlock_percent as i128 / 100;
+    let remaining = schedule.total_amount - initial_unlock;
+    
+    let total_vesting_time = schedule.end_ledger - schedule.start_ledger;
+    let elapsed_time = current_ledger - schedule.start_ledger;
+    
+    if schedule.period_ledgers == 0 {
+        // Lineer vesting: Geçen zamana orantılı olarak token miktarı hesaplanır
+        return initial_unlock + remaining * elapsed_time as i128 / total_vesting_time as i128;
+    }
+    
+    // Kademeli vesting: Sadece tamamlanan dönemler kadar dilim açılır
+    let total_periods = total_vesting_time.div_ceil(schedule.period_ledgers);
+    let elapsed_periods = elapsed_time / schedule.period_ledgers;
+    
+    initial_unlock + remaining * elapsed_periods as i128 / total_periods as i128
+}
+
+// Bir vesting planında şu an çekilebilir miktarı hesaplayan yardımcı fonksiyon
+fn get_claimable_amount(e: &Env, schedule: &VestingSchedule) -> i128 {
+    let vested_amount = get_vested_amount(e, schedule);
+    
+    // Şimdiye kadar çekilen miktarı çıkaralım
+    if vested_amount <= schedule.claimed_amount {
+        return 0;
+    }
+    
+    vested_amount - schedule.claimed_amount
 }
 
 #[contract]
@@ -99,13 +388,30 @@ pub struct Token;
 
 #[contractimpl]
 impl Token {
-    pub fn initialize(e: Env, admin: Address, decimal: u32, name: String, symbol: String) {
+    // clawback_enabled sonradan değiştirilemez, böylece token sahipleri clawback
+    // yapılıp yapılamayacağını kurulumdan itibaren bilir.
+    pub fn initialize(
+        e: Env,
+        admin: Address,
+        decimal: u32,
+        name: String,
+        symbol: String,
+        clawback_enabled: bool,
+    ) {
         if has_administrator(&e) {
-            panic!("already initialized")
+            panic_with_error!(&e, ContractError::AlreadyInitialized)
         }
         write_administrator(&e, &admin);
+        write_schema_version(&e, CURRENT_SCHEMA_VERSION);
+        write_total_supply(&e, 0);
+        write_clawback_enabled(&e, clawback_enabled);
         if decimal > u8::MAX.into() {
-            panic!("Decimal must fit in a u8");
+            panic_with_error!(&e, ContractError::DecimalTooLarge);
+        }
+
+        // Başlangıçta tüm roller admin'e verilir, admin bunları başka hesaplara dağıtabilir
+        for role in ALL_ROLES {
+            write_role(&e, role, &admin);
         }
 
         write_metadata(
@@ -118,95 +424,757 @@ impl Token {
         )
     }
 
-    pub fn mint(e: Env, to: Address, amount: i128) {
-        check_nonnegative_amount(amount);
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    pub fn mint(e: Env, caller: Address, to: Address, amount: i128) {
+        caller.require_auth();
+        check_role(&e, Role::Minter, &caller);
+        Self::mint_tokens(&e, caller, to, amount);
+    }
+
+    fn mint_tokens(e: &Env, minter: Address, to: Address, amount: i128) {
+        check_nonnegative_amount(e, amount);
+        check_not_paused(e, PauseOperation::MintBurn);
+        check_can_receive(e, &to);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Arz sınırı varsa aşılmamalı (arz kaydı olmayan eski kurulumlarda mint migrate'e kadar reddedilir)
+        let total_supply = read_seeded_total_supply(e)
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(e, SupplyError::SupplyCapExceeded));
+        if let Some(max_supply) = read_max_supply(e) {
+            if total_supply > max_supply {
+                panic_with_error!(e, SupplyError::SupplyCapExceeded);
+            }
+        }
+        write_total_supply(e, total_supply);
+
+        // Minter için sınır tanımlıysa pencere içindeki toplam mint sınırı aşmamalı
+        if let Some(limit) = read_mint_limit(e, &minter) {
+            let (mut history, minted) = mint_history_in_window(e, &minter, &limit);
+            if minted + amount > limit.amount {
+                panic_with_error!(e, SupplyError::MintLimitExceeded);
+            }
+            if amount > 0 {
+                let ledger = e.ledger().sequence();
+                let bucket_start = ledger - ledger % mint_bucket_ledgers(&limit);
+                match history.last() {
+                    Some(mut last) if last.ledger == bucket_start => {
+                        last.amount += amount;
+                        history.set(history.len() - 1, last);
+                    }
+                    _ => history.push_back(MintRecord { ledger: bucket_start, amount }),
+                }
+            }
+            write_mint_history(e, &minter, &history);
+        }
+
+        receive_balance(e, to.clone(), amount);
+        TokenUtils::new(e).events().mint(minter, to, amount);
+    }
+
+    // Bir hesaptaki tokenlere el koy (sadece uyum rolü, clawback kurulumda açıldıysa).
+    // recipient verilirse tokenler ona aktarılır, verilmezse yakılır. Dondurulmuş
+    // hesaplarda ve işlemler durdurulmuşken de çalışır.
+    pub fn clawback(
+        e: Env,
+        caller: Address,
+        from: Address,
+        amount: i128,
+        recipient: Option<Address>,
+        reason: Symbol,
+    ) {
+        caller.require_auth();
+        check_role(&e, Role::Compliance, &caller);
+
+        if !read_clawback_enabled(&e) {
+            panic_with_error!(&e, SupplyError::ClawbackDisabled);
+        }
+        check_nonnegative_amount(&e, amount);
+        // Aktarılan tokenler transferdeki gibi sadece token alabilen hesaba gidebilir
+        if let Some(recipient) = &recipient {
+            check_can_receive(&e, recipient);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        spend_balance(&e, from.clone(), amount);
+        TokenUtils::new(&e).events().clawback(caller.clone(), from.clone(), amount);
+
+        // Aktarılan tokenler dolaşımda kalır, yakılanlar arzdan düşülür
+        match recipient {
+            Some(recipient) => {
+                receive_balance(&e, recipient.clone(), amount);
+                TokenUtils::new(&e).events().mint(caller.clone(), recipient, amount);
+            }
+            None => reduce_total_supply(&e, amount),
+        }
+
+        e.events().publish(("clawback_reason", caller, from), (reason, amount));
+    }
+
+    // Kontratın clawback yapıp yapamayacağı (kurulumda belirlenir)
+    pub fn clawback_enabled(e: Env) -> bool {
+        read_clawback_enabled(&e)
+    }
+
+    // Dolaşımdaki toplam token miktarı (mint edilen eksi yakılan)
+    pub fn total_supply(e: Env) -> i128 {
+        read_total_supply(&e)
+    }
+
+    // Mint edilebilecek en fazla toplam arz (None ise sınır yok)
+    pub fn max_supply(e: Env) -> Option<i128> {
+        read_max_supply(&e)
+    }
+
+    // Minter'ın sınırını getir (None ise minter sınırsız mint edebilir)
+    pub fn get_mint_limit(e: Env, minter: Address) -> Option<MintLimit> {
+        read_mint_limit(&e, &minter)
+    }
+
+    // Minter'ın mevcut pencerede daha ne kadar mint edebileceği (None ise sınır yok)
+    pub fn remaining_mint_capacity(e: Env, minter: Address) -> Option<i128> {
+        let limit = read_mint_limit(&e, &minter)?;
+        let (_, minted) = mint_history_in_window(&e, &minter, &limit);
+        Some((limit.amount - minted).max(0))
+    }
+
+    // Bir minter'ın kayan pencere sınırını güncelle (timelock üzerinden, sadece admin
+    // sıraya alabilir). Yeni sınır mevcut penceredeki mint kayıtlarına da uygulanır.
+    fn update_mint_limit(e: &Env, minter: Address, limit: Option<MintLimit>) {
+        if let Some(limit) = &limit {
+            if limit.amount < 0 || limit.window_ledgers == 0 {
+                panic_with_error!(e, SupplyError::InvalidMintLimit);
+            }
+        }
+
+        write_mint_limit(e, &minter, &limit);
+        e.events().publish(("update_mint_limit", minter), limit);
+    }
+
+    // Arz sınırını güncelle (timelock üzerinden, sadece admin sıraya alabilir).
+    // Sınır mevcut toplam arzın altına indirilemez.
+    fn update_max_supply(e: &Env, max_supply: Option<i128>) {
+        if let Some(max_supply) = max_supply {
+            if max_supply < 0 || max_supply < read_seeded_total_supply(e) {
+                panic_with_error!(e, SupplyError::InvalidMaxSupply);
+            }
+        }
+
+        write_max_supply(e, max_supply);
+        e.events().publish(("update_max_supply",), max_supply);
+    }
+
+    // Admin devrini başlat (timelock üzerinden, sadece mevcut admin sıraya alabilir). Devir,
+    // yeni admin accept_admin ile kabul edene kadar gerçekleşmez. Yeni bir teklif öncekinin yerini alır.
+    fn propose_admin(e: Env, new_admin: Address, expiration_ledger: Option<u32>) {
+        let admin = read_administrator(&e);
+
+        if let Some(expiration_ledger) = expiration_ledger {
+            if expiration_ledger < e.ledger().sequence() {
+                panic_with_error!(&e, ContractError::InvalidExpirationLedger);
+            }
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_pending_admin(
+            &e,
+            &PendingAdmin {
+                new_admin: new_admin.clone(),
+                expiration_ledger,
+            },
+        );
+        e.events().publish(("propose_admin", admin, new_admin), expiration_ledger);
+    }
+
+    // Bekleyen admin devrini kabul et (sadece önerilen yeni admin yapabilir)
+    pub fn accept_admin(e: Env) {
+        let pending = read_pending_admin(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::NoPendingAdmin));
+        pending.new_admin.require_auth();
+
+        if let Some(expiration_ledger) = pending.expiration_ledger {
+            if e.ledger().sequence() > expiration_ledger {
+                panic_with_error!(&e, ContractError::AdminProposalExpired);
+            }
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Eski admin'in elinde kalan roller de yeni admin'e geçer, böylece devirden
+        // sonra eski hesap mint, hazine veya uyum işlemlerini yapamaz
+        let admin = read_administrator(&e);
+        remove_pending_admin(&e);
+        write_administrator(&e, &pending.new_admin);
+        transfer_roles(&e, &admin, &pending.new_admin);
+        // Konsey yetkiyi devrettiyse artık teklif uygulayamaz
+        if admin == e.current_contract_address() {
+            remove_council(&e);
+        }
+        TokenUtils::new(&e).events().set_admin(admin, pending.new_admin);
+    }
+
+    // Bekleyen admin devrini iptal et (sadece mevcut admin yapabilir)
+    pub fn cancel_admin_proposal(e: Env) {
+        let admin = require_admin(&e);
+        Self::remove_admin_proposal(&e, admin);
+    }
+
+    // Konsey de aynı yoldan iptal eder
+    fn remove_admin_proposal(e: &Env, admin: Address) {
+        let pending = read_pending_admin(e)
+            .unwrap_or_else(|| panic_with_error!(e, ContractError::NoPendingAdmin));
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        remove_pending_admin(e);
+        e.events().publish(("cancel_admin_proposal", admin, pending.new_admin), ());
+    }
+
+    // Bekleyen admin devri teklifini getir
+    pub fn get_pending_admin(e: Env) -> Option<PendingAdmin> {
+        read_pending_admin(&e)
+    }
+
+    // Kontratın WASM kodunu yükseltilmiş sürümle değiştir (timelock üzerinden, sadece
+    // admin sıraya alabilir). Yeni sürüm storage düzenini değiştiriyorsa ardından
+    // yeni koddaki migrate() çağrılmalıdır.
+    fn upgrade_wasm(e: &Env, admin: Address, wasm_hash: BytesN<32>) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.deployer().update_current_contract_wasm(wasm_hash.clone());
+        e.events().publish(("upgrade", admin), wasm_hash);
+    }
+
+    // Kayıtlı şema sürümünden güncel sürüme kadar olan taşıma adımlarını sırayla
+    // çalıştır (sadece admin yapabilir). Her adım sadece bir kez çalışır, şema
+    // zaten güncelse çağrı reddedilir. Ulaşılan şema sürümünü döndürür. `total_supply`
+    // sadece toplam arz kaydı olmayan eski kurulumlarda gereklidir.
+    pub fn migrate(e: Env, total_supply: Option<i128>) -> u32 {
+        let admin = require_admin(&e);
+        Self::run_migrations(&e, admin, total_supply)
+    }
+
+    fn run_migrations(e: &Env, admin: Address, total_supply: Option<i128>) -> u32 {
+        let from_version = read_schema_version(e);
+        if from_version >= CURRENT_SCHEMA_VERSION {
+            panic_with_error!(e, SchemaError::AlreadyMigrated);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        for version in from_version..CURRENT_SCHEMA_VERSION {
+            // 0 -> 1: staking'in ayrı admin kaydı yerini rollere bıraktı
+            if version == 0 {
+                e.storage().instance().remove(&LEGACY_ADMIN_KEY);
+            }
+            // 1 -> 2: vesting planları ve lending havuzu persistent storage'a taşındı.
+            // Hesap bazlı dondurma ve lending kayıtları sayılamadığı için ilk
+            // yazıldıklarında taşınır, o zamana kadar eski kayıttan okunur.
+            if version == 1 {
+                migrate_vesting_to_persistent(e);
+            }
+            // 2 -> 3: tek lending havuzu kontratın kendi tokeninin piyasası oldu. Eski
+            // sürümlerden gelen havuz da bu adımda doğrudan piyasa anahtarına taşınır.
+            if version == 2 {
+                migrate_legacy_pool_to_market(e);
+            }
+            // 3 -> 4: staking havuzu token başına ödül birikimine geçti. Eski düzendeki
+            // havuz yeni yapıya çevrilir, eski oran da taşınmamış stake'lerin o ana
+            // kadar biriken ödülü migrate_stakes'te hesaplanabilsin diye saklanır.
+            if version == 3 {
+                Self::migrate_legacy_pool_info(e);
+            }
+            // 4 -> 5: arz takibinden önce kurulan kontratların toplam arzı admin'in
+            // verdiği denetlenmiş miktarla başlatılır
+            if version == 4 {
+                migrate_total_supply(e, total_supply);
+            }
+            // 5 -> 6: piyasalar protokol rezervini ayrı bir alanda tutmaya başladı
+            if version == 5 {
+                migrate_market_reserves(e);
+            }
+            // 6 -> 7: borç pozisyonlarındaki teminat toplamı acil çekimlerden korunmak
+            // için ayrıca tutulmaya başladı
+            if version == 6 {
+                Self::migrate_total_collateral(e);
+            }
+            // 7 -> 8: vesting planları iptalde iade için fonlayan hesabı tutmaya başladı
+            if version == 7 {
+                migrate_vesting_funders(e);
+            }
+        }
+
+        write_schema_version(e, CURRENT_SCHEMA_VERSION);
+        e.events().publish(("migrate", admin), (from_version, CURRENT_SCHEMA_VERSION));
+        CURRENT_SCHEMA_VERSION
+    }
+
+    // Storage düzeninin şema sürümünü getir
+    pub fn get_schema_version(e: Env) -> u32 {
+        read_schema_version(&e)
+    }
+
+    // Bir hesaba rol ver (sadece admin yapabilir)
+    pub fn grant_role(e: Env, role: Role, account: Address) {
+        let admin = require_admin(&e);
+        Self::grant_role_as(&e, admin, role, account);
+    }
+
+    fn grant_role_as(e: &Env, admin: Address, role: Role, account: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(e, role, &account);
+        e.events().publish(("grant_role", admin, account), role);
+    }
+
+    // Bir hesaptan rolü geri al (sadece admin yapabilir)
+    pub fn revoke_role(e: Env, role: Role, account: Address) {
+        let admin = require_admin(&e);
+        Self::revoke_role_as(&e, admin, role, account);
+    }
+
+    fn revoke_role_as(e: &Env, admin: Address, role: Role, account: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        remove_role(e, role, &account);
+        e.events().publish(("revoke_role", admin, account), role);
+    }
+
+    // Bir hesabın belirtilen role sahip olup olmadığını getir
+    pub fn has_role(e: Env, role: Role, account: Address) -> bool {
+        has_role(&e, role, &account)
+    }
+
+    // Bir hesabı verilen yönde dondur (sadece freezer rolü yapabilir). Hesap zaten
+    // dondurulmuşsa mod ve sebep kodu güncellenir.
+    pub fn freeze_account(e: Env, caller: Address, account: Address, mode: FreezeMode, reason: u32) {
+        caller.require_auth();
+        check_role(&e, Role::Freezer, &caller);
+
+        // Kontrat örneğinin TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Hesabı dondurulmuş olarak ayarla
+        let status = FreezeStatus {
+            mode,
+            reason,
+            since_ledger: e.ledger().sequence(),
+        };
+        write_freeze_status(&e, &account, &status);
+
+        // Dondurma olayını yayınla
+        e.events().publish(("freeze_account", caller, account), (mode, reason));
+    }
+
+    // Bir hesabın dondurulmasını kaldır (sadece freezer rolü yapabilir)
+    pub fn unfreeze_account(e: Env, caller: Address, account: Address) {
+        caller.require_auth();
+        check_role(&e, Role::Freezer, &caller);
+
+        // Kontrat örneğinin TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Dondurulmuş durumu kaldır
+        remove_freeze_status(&e, &account);
+
+        // Dondurma kaldırma olayını yayınla
+        emit_custom_event(&e, "unfreeze_account", caller, account);
+    }
+
+    // Hesabın dondurma durumunu getir (None ise hesap dondurulmamış)
+    pub fn get_freeze_status(e: Env, account: Address) -> Option<FreezeStatus> {
+        read_freeze_status(&e, &account)
+    }
+
+    // Bir işlem grubunu durdur (sadece pauser rolü yapabilir)
+    pub fn pause(e: Env, caller: Address, operation: PauseOperation) {
+        caller.require_auth();
+        check_role(&e, Role::Pauser, &caller);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_paused(&e, operation, true);
+        e.events().publish(("pause", caller), operation);
+    }
+
+    // Durdurulmuş bir işlem grubunu yeniden başlat (sadece pauser rolü yapabilir)
+    pub fn unpause(e: Env, caller: Address, operation: PauseOperation) {
+        caller.require_auth();
+        check_role(&e, Role::Pauser, &caller);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_paused(&e, operation, false);
+        e.events().publish(("unpause", caller), operation);
+    }
+
+    // Bir işlem grubunun durdurulup durdurulmadığını getir
+    pub fn is_paused(e: Env, operation: PauseOperation) -> bool {
+        read_paused(&e, operation)
+    }
+
+    // Hassas bir parametre değişikliğini TIMELOCK_DELAY sonrası için sıraya al
+    pub fn queue_operation(e: Env, caller: Address, operation: TimelockOperation) -> u64 {
+        caller.require_auth();
+        check_timelock_access(&e, &caller, &operation);
+        Self::enqueue_operation(&e, caller, operation)
+    }
+
+    fn enqueue_operation(e: &Env, proposer: Address, operation: TimelockOperation) -> u64 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let queued = QueuedOperation {
+            id: next_operation_id(e),
+            operation,
+            proposer: proposer.clone(),
+            eta_ledger: e.ledger().sequence() + TIMELOCK_DELAY,
+        };
+
+        let mut operations = read_queued_operations(e);
+        operations.push_back(queued.clone());
+        write_queued_operations(e, &operations);
+
+        e.events().publish(
+            ("queue_operation", proposer),
+            (queued.id, queued.operation, queued.eta_ledger),
+        );
+        queued.id
+    }
+
+    // Gecikmesi dolmuş bir işlemi uygula (herkes çağırabilir)
+    pub fn execute_operation(e: Env, operation_id: u64) {
+        let mut operations = read_queued_operations(&e);
+        let index = operations
+            .iter()
+            .position(|queued| queued.id == operation_id)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::OperationNotFound)) as u32;
+        let queued = operations.get_unchecked(index);
+
+        if e.ledger().sequence() < queued.eta_ledger {
+            panic_with_error!(&e, ContractError::OperationNotReady);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        operations.remove(index);
+        write_queued_operations(&e, &operations);
+
+        match queued.operation {
+            TimelockOperation::UpdateLendingRates(asset, supply_rate, borrow_rate) => {
+                Self::update_lending_rates(e.clone(), queued.proposer, asset, supply_rate, borrow_rate)
+            }
+            TimelockOperation::UpdateInterestRateModel(asset, model) => {
+                Self::update_interest_rate_model(e.clone(), queued.proposer, asset, model)
+            }
+            TimelockOperation::UpdateOracle(config) => Self::update_oracle(e.clone(), queued.proposer, config),
+            TimelockOperation::UpdateCollateralFactor(asset, factor) => {
+                Self::update_collateral_factor(e.clone(), queued.proposer, asset, factor)
+            }
+            TimelockOperation::UpdateLiquidationParams(threshold, penalty) => {
+                Self::update_liquidation_params(e.clone(), queued.proposer, threshold, penalty)
+            }
+            TimelockOperation::UpdateRewardDuration(duration) => {
+                Self::update_reward_duration(e.clone(), queued.proposer, duration)
+            }
+            TimelockOperation::ProposeAdmin(new_admin, expiration_ledger) => {
+                Self::propose_admin(e.clone(), new_admin, expiration_ledger)
+            }
+            TimelockOperation::UpdateMaxSupply(max_supply) => {
+                Self::update_max_supply(&e, max_supply)
+            }
+            TimelockOperation::UpdateMintLimit(minter, limit) => {
+                Self::update_mint_limit(&e, minter, Some(limit))
+            }
+            TimelockOperation::RemoveMintLimit(minter) => Self::update_mint_limit(&e, minter, None),
+            TimelockOperation::Upgrade(wasm_hash) => Self::upgrade_wasm(&e, queued.proposer, wasm_hash),
+        }
+
+        e.events().publish(("execute_operation",), operation_id);
+    }
+
+    // Sıradaki bir işlemi iptal et (işlemi sıraya alabilecek rol yapabilir)
+    pub fn cancel_operation(e: Env, caller: Address, operation_id: u64) {
+        caller.require_auth();
+        Self::dequeue_operation(&e, caller, operation_id);
+    }
+
+    // Konsey kurulduktan sonra admin kontrat adresidir; konsey işlemleri bu yoldan
+    // kontrat adresi adına iptal eder
+    fn dequeue_operation(e: &Env, caller: Address, operation_id: u64) {
+        let mut operations = read_queued_operations(e);
+        let index = operations
+            .iter()
+            .position(|queued| queued.id == operation_id)
+            .unwrap_or_else(|| panic_with_error!(e, ContractError::OperationNotFound)) as u32;
+        check_timelock_access(e, &caller, &operations.get_unchecked(index).operation);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        operations.remove(index);
+        write_queued_operations(e, &operations);
+
+        e.events().publish(("cancel_operation", caller), operation_id);
+    }
+
+    // Sırada bekleyen tüm timelock işlemlerini getir
+    pub fn get_queued_operations(e: Env) -> Vec<QueuedOperation> {
+        read_queued_operations(&e)
+    }
+
+    // Admin konseyini kur (sadece admin, bir kez yapabilir). Admin yetkisi ve admin'in
+    // rolleri kontratın kendisine geçer; bundan sonra admin işlemleri, rol değişiklikleri,
+    // yükseltme ve migrate sadece eşiğe ulaşan konsey teklifleriyle yapılabilir.
+    pub fn set_council(e: Env, signers: Vec<Address>, threshold: u32) {
+        let admin = require_admin(&e);
+
+        if read_council(&e).is_some() {
+            panic_with_error!(&e, ContractError::CouncilAlreadyInitialized);
+        }
+        validate_council(&e, &signers, threshold);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        receive_balance(&e, to.clone(), amount);
-        TokenUtils::new(&e).events().mint(admin, to, amount);
+        write_council(&e, &Council { signers: signers.clone(), threshold });
+
+        let council_address = e.current_contract_address();
+        write_administrator(&e, &council_address);
+        transfer_roles(&e, &admin, &council_address);
+        // Eski admin'in başlattığı devir ve sıraya aldığı işlemler konsey onayından
+        // geçmediği için iptal edilir
+        remove_pending_admin(&e);
+        let mut operations = Vec::new(&e);
+        for queued in read_queued_operations(&e).iter() {
+            if queued.proposer != admin {
+                operations.push_back(queued);
+            }
+        }
+        write_queued_operations(&e, &operations);
+
+        e.events().publish(("set_council", admin), (signers, threshold));
     }
 
-    pub fn set_admin(e: Env, new_admin: Address) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    // Konsey adına bir admin işlemi öner. Öneren imzacının onayı otomatik sayılır.
+    pub fn propose(e: Env, signer: Address, action: CouncilAction) -> u64 {
+        signer.require_auth();
+        let council = read_council_for_signer(&e, &signer);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        write_administrator(&e, &new_admin);
-        TokenUtils::new(&e).events().set_admin(admin, new_admin);
+        let proposal = CouncilProposal {
+            id: next_council_proposal_id(&e),
+            action,
+            approvals: Vec::from_array(&e, [signer.clone()]),
+        };
+        e.events().publish(("propose", signer), (proposal.id, proposal.action.clone()));
+
+        let proposal_id = proposal.id;
+        Self::apply_council_approvals(&e, &council, proposal);
+        proposal_id
     }
 
-    // Bir hesabı dondur (sadece yönetici yapabilir)
-    pub fn freeze_account(e: Env, account: Address) {
-        // Sadece yönetici hesapları dondurabilir
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    // Bir konsey teklifini onayla. Eşiğe ulaşan teklif hemen uygulanır ve true döner.
+    pub fn approve_proposal(e: Env, signer: Address, proposal_id: u64) -> bool {
+        signer.require_auth();
+        let council = read_council_for_signer(&e, &signer);
+
+        let mut proposal = read_council_proposal(&e, proposal_id)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::ProposalNotFound));
+        if proposal.approvals.contains(&signer) {
+            panic_with_error!(&e, ContractError::AlreadyApproved);
+        }
 
-        // Kontrat örneğinin TTL süresini uzat
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        // Hesabı dondurulmuş olarak ayarla
-        let key = DataKey::Frozen(account.clone());
-        e.storage().instance().set(&key, &true);
+        proposal.approvals.push_back(signer.clone());
+        e.events().publish(("approve_proposal", signer), proposal_id);
 
-       // Dondurma olayını yayınla
-       emit_custom_event(&e, "freeze_account", admin, account);
+        Self::apply_council_approvals(&e, &council, proposal)
     }
 
-    // Bir hesabın dondurulmasını kaldır (sadece yönetici yapabilir)
-    pub fn unfreeze_account(e: Env, account: Address) {
-        // Sadece yönetici hesapların dondurulmasını kaldırabilir
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    // Güncel imzacıların onayları eşiğe ulaştıysa teklifi uygula, aksi halde kaydet
+    fn apply_council_approvals(e: &Env, council: &Council, proposal: CouncilProposal) -> bool {
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|approver| council.signers.contains(approver))
+            .count() as u32;
+        if approvals < council.threshold {
+            write_council_proposal(e, &proposal);
+            return false;
+        }
 
-        // Kontrat örneğinin TTL süresini uzat
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        remove_council_proposal(e, proposal.id);
 
-        // Dondurulmuş durumu kaldır
-        let key = DataKey::Frozen(account.clone());
-        e.storage().instance().remove(&key);
+        // Konsey işlemleri kontrat adresi adına gerçekleştirilir
+        let council_address = e.current_contract_address();
+        match proposal.action {
+            CouncilAction::Mint(to, amount) => Self::mint_tokens(e, council_address, to, amount),
+            CouncilAction::EmergencyWithdrawRewards(recipient) => {
+                Self::withdraw_reward_funds(e.clone(), recipient);
+            }
+            CouncilAction::EmergencyWithdrawLendingPool(asset, recipient) => {
+                Self::withdraw_lending_funds(e.clone(), asset, recipient);
+            }
+            CouncilAction::QueueOperation(operation) => {
+                Self::enqueue_operation(e, council_address, operation);
+            }
+            CouncilAction::UpdateCouncil(signers, threshold) => {
+                validate_council(e, &signers, threshold);
+                write_council(e, &Council { signers, threshold });
+            }
+            CouncilAction::GrantRole(role, account) => {
+                Self::grant_role_as(e, council_address, role, account)
+            }
+            CouncilAction::RevokeRole(role, account) => {
+                Self::revoke_role_as(e, council_address, role, account)
+            }
+            CouncilAction::CancelOperation(operation_id) => {
+                Self::dequeue_operation(e, council_address, operation_id);
+            }
+            CouncilAction::CancelAdminProposal => {
+                Self::remove_admin_proposal(e, council_address);
+            }
+            CouncilAction::Migrate(total_supply) => {
+                Self::run_migrations(e, council_address, total_supply);
+            }
+        }
 
-        // Dondurma kaldırma olayını yayınla
-        emit_custom_event(&e, "unfreeze_account", admin, account);
+        e.events().publish(("execute_proposal",), proposal.id);
+        true
+    }
+
+    // Admin konseyini getir
+    pub fn get_council(e: Env) -> Option<Council> {
+        read_council(&e)
+    }
+
+    // Bekleyen bir konsey teklifini getir
+    pub fn get_council_proposal(e: Env, proposal_id: u64) -> Option<CouncilProposal> {
+        read_council_proposal(&e, proposal_id)
     }
 
     pub fn create_vesting(
         e: Env,
+        caller: Address,
         beneficiary: Address,
         amount: i128,
         start_ledger: u32,
         cliff_ledger: u32,
         end_ledger: u32,
-    ) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+        revocable: bool,
+    ) -> u64 {
+        Self::add_vesting(
+            &e,
+            &caller,
+            VestingParams {
+                beneficiary,
+                amount,
+                start_ledger,
+                cliff_ledger,
+                end_ledger,
+                period_ledgers: 0,
+                initial_unlock_percent: 0,
+                revocable,
+            },
+        )
+    }
+    
+    // Kademeli (dönemsel dilimlerle açılan) vesting planı oluştur (sadece vesting yöneticisi yapabilir)
+    pub fn create_periodic_vesting(
+        e: Env,
+        caller: Address,
+        beneficiary: Address,
+        amount: i128,
+        start_ledger: u32,
+        cliff_ledger: u32,
+        end_ledger: u32,
+        period_ledgers: u32,
+        initial_unlock_percent: u32,
+        revocable: bool,
+    ) -> u64 {
+        // Dönem adımı sıfır olamaz
+        if period_ledgers == 0 {
+            panic_with_error!(&e, ContractError::InvalidVestingPeriod);
+        }
         
-        check_nonnegative_amount(amount);
+        Self::add_vesting(
+            &e,
+            &caller,
+            VestingParams {
+                beneficiary,
+                amount,
+                start_ledger,
+                cliff_ledger,
+                end_ledger,
+                period_ledgers,
+                initial_unlock_percent,
+                revocable,
+            },
+        )
+    }
+    
+    // Token dağıtım etkinlikleri için tek çağrıda birden fazla vesting planı oluştur
+    // (sadece vesting yöneticisi yapabilir). Tüm girdiler önce doğrulanır, fonlayan
+    // hesabın bakiyesi toplam miktar için bir kez kontrol edilir ve her plan için ayrı bir olay yayınlanır.
+    // Bir çağrıdaki plan sayısı MAX_VESTING_BATCH_SIZE ile sınırlıdır.
+    pub fn create_vesting_batch(e: Env, caller: Address, grants: Vec<VestingParams>) -> Vec<u64> {
+        caller.require_auth();
+        check_role(&e, Role::VestingManager, &caller);
         
-        // Parametrelerin mantıklı olduğunu kontrol et
-        if end_ledger <= start_ledger {
-            panic!("Bitiş ledger'ı başlangıç ledger'ından büyük olmalıdır");
+        if grants.len() > MAX_VESTING_BATCH_SIZE {
+            panic_with_error!(&e, ContractError::VestingBatchTooLarge);
         }
         
-        if cliff_ledger > 0 && cliff_ledger < start_ledger {
-            panic!("Cliff ledger'ı başlangıç ledger'ından küçük olamaz");
+        // Herhangi bir plan yazılmadan önce tüm girdileri doğrula
+        let mut total_amount: i128 = 0;
+        for params in grants.iter() {
+            validate_vesting_params(&e, &params);
+            total_amount += params.amount;
         }
         
-        // Yöneticinin yeterli token'a sahip olduğunu kontrol et
-        let admin_balance = read_balance(&e, admin.clone());
-        if admin_balance < amount {
-            panic!("Yönetici vesting için yeterli token'a sahip değil");
+        // Fonlayan hesabın tüm planlar için yeterli token'a sahip olduğunu kontrol et
+        if read_balance(&e, caller.clone()) < total_amount {
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
         
         // TTL süresini uzat
@@ -214,33 +1182,67 @@ impl Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        // Vesting planını kaydet
-        let schedule = VestingSchedule {
-            beneficiary: beneficiary.clone(),
-            total_amount: amount,
-            claimed_amount: 0,
-            start_ledger,
-            cliff_ledger,
-            end_ledger,
-        };
+        // Toplam miktarı tek seferde emanete al
+        spend_balance(&e, caller.clone(), total_amount);
+        receive_balance(&e, e.current_contract_address(), total_amount);
+        write_vesting_escrow(&e, read_vesting_escrow(&e) + total_amount);
+        
+        let mut schedule_ids = Vec::new(&e);
+        for params in grants.iter() {
+            schedule_ids.push_back(store_vesting_schedule(&e, &caller, params));
+        }
+        schedule_ids
+    }
+    
+    fn add_vesting(e: &Env, caller: &Address, params: VestingParams) -> u64 {
+        caller.require_auth();
+        check_role(e, Role::VestingManager, caller);
+        
+        validate_vesting_params(e, &params);
+        
+        // Fonlayan hesabın yeterli token'a sahip olduğunu kontrol et
+        if read_balance(e, caller.clone()) < params.amount {
+            panic_with_error!(e, ContractError::InsufficientBalance);
+        }
+        
+        // TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        write_vesting_schedule(&e, &beneficiary, &schedule);
+        // Vesting için ayrılan token'ları fonlayan hesaptan al ve kontratta emanet olarak tut
+        spend_balance(e, caller.clone(), params.amount);
+        receive_balance(e, e.current_contract_address(), params.amount);
+        write_vesting_escrow(e, read_vesting_escrow(e) + params.amount);
+        
+        store_vesting_schedule(e, caller, params)
+    }
+    
+    // Hak edilen token'ları emanetten faydalanıcıya aktar ve planı güncelle
+    fn release_vesting(e: &Env, schedule: &mut VestingSchedule, amount: i128) {
+        schedule.claimed_amount += amount;
         
-        // Vesting için ayrılan token'ları yöneticiden al (dondurulmuş olarak saklanacak)
-        spend_balance(&e, admin.clone(), amount);
-        receive_balance(&e, beneficiary.clone(), amount);
+        // Eğer tüm tokenlar çekildiyse vesting planını kaldır
+        if schedule.claimed_amount >= schedule.total_amount {
+            remove_vesting_schedule(e, schedule);
+        } else {
+            write_vesting_schedule(e, schedule);
+        }
         
-        // Hesabı otomatik olarak dondur
-        let key = DataKey::Frozen(beneficiary.clone());
-        e.storage().instance().set(&key, &true);
+        spend_balance(e, e.current_contract_address(), amount);
+        receive_balance(e, schedule.beneficiary.clone(), amount);
+        write_vesting_escrow(e, read_vesting_escrow(e) - amount);
         
-        // Vesting oluşturma olayını yayınla
-        emit_custom_event(&e, "create_vesting", admin, beneficiary);
+        e.events().publish(("claim_vesting", schedule.beneficiary.clone()), (schedule.id, amount));
     }
     
-    // Vesting planından token'ları talep et (sadece faydalanıcı yapabilir)
-    pub fn claim_vesting(e: Env, beneficiary: Address) -> i128 {
-        beneficiary.require_auth();
+    // Tek bir vesting planından token'ları talep et (sadece faydalanıcı yapabilir)
+    pub fn claim_vesting(e: Env, schedule_id: u64) -> i128 {
+        let mut schedule = read_vesting_schedule(&e, schedule_id)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::VestingNotFound));
+        schedule.beneficiary.require_auth();
+        check_not_paused(&e, PauseOperation::VestingClaim);
+        check_can_receive(&e, &schedule.beneficiary);
         
         // TTL süresini uzat
         e.storage()
@@ -248,156 +1250,290 @@ impl Token {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
         // Çekilebilir miktarı hesapla
-        let claimable_amount = get_claimable_amount(&e, &beneficiary);
+        let claimable_amount = get_claimable_amount(&e, &schedule);
         
         if claimable_amount <= 0 {
-            panic!("Çekilebilir token bulunmamaktadır");
+            panic_with_error!(&e, ContractError::NothingToClaim);
         }
         
-        // Vesting planını güncelle
-        if let Some(mut schedule) = read_vesting_schedule(&e, &beneficiary) {
-            schedule.claimed_amount += claimable_amount;
-            
-            // Eğer tüm tokenlar çekildiyse vesting planını kaldır ve hesabı çöz
-            if schedule.claimed_amount >= schedule.total_amount {
-                remove_vesting_schedule(&e, &beneficiary);
-                
-                // Hesabın dondurulmasını kaldır
-                let key = DataKey::Frozen(beneficiary.clone());
-                e.storage().instance().remove(&key);
-            } else {
-                write_vesting_schedule(&e, &beneficiary, &schedule);
+        Self::release_vesting(&e, &mut schedule, claimable_amount);
+        
+        claimable_amount
+    }
+    
+    // Faydalanıcının tüm vesting planlarındaki çekilebilir token'ları talep et
+    pub fn claim_all_vesting(e: Env, beneficiary: Address) -> i128 {
+        beneficiary.require_auth();
+        check_not_paused(&e, PauseOperation::VestingClaim);
+        check_can_receive(&e, &beneficiary);
+        
+        // TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        let mut total_claimed = 0;
+        for schedule_id in read_vesting_ids(&e, &beneficiary).iter() {
+            if let Some(mut schedule) = read_vesting_schedule(&e, schedule_id) {
+                let claimable_amount = get_claimable_amount(&e, &schedule);
+                if claimable_amount > 0 {
+                    Self::release_vesting(&e, &mut schedule, claimable_amount);
+                    total_claimed += claimable_amount;
+                }
             }
-            
-            // Donmuş token'ların çözülmesini sağla
-            let admin = read_administrator(&e);
-            
-            // Özel bir olay yayınla
-            emit_custom_event(&e, "claim_vesting", admin, beneficiary);
-            
-            return claimable_amount;
         }
         
-        panic!("Vesting planı bulunamadı");
+        if total_claimed <= 0 {
+            panic_with_error!(&e, ContractError::NothingToClaim);
+        }
+        
+        total_claimed
     }
     
-    // Faydalanıcı için vesting planı bilgilerini getir
-    pub fn get_vesting_info(e: Env, beneficiary: Address) -> Option<VestingSchedule> {
+    // Vesting planı bilgilerini getir
+    pub fn get_vesting_info(e: Env, schedule_id: u64) -> Option<VestingSchedule> {
         // TTL süresini uzat
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        read_vesting_schedule(&e, &beneficiary)
+        read_vesting_schedule(&e, schedule_id)
     }
     
-    // Çekilebilir vesting miktarını hesapla
-    pub fn get_claimable_vesting(e: Env, beneficiary: Address) -> i128 {
+    // Faydalanıcının aktif tüm vesting planlarını getir
+    pub fn list_vesting_schedules(e: Env, beneficiary: Address) -> Vec<VestingSchedule> {
         // TTL süresini uzat
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        get_claimable_amount(&e, &beneficiary)
+        let mut schedules = Vec::new(&e);
+        for schedule_id in read_vesting_ids(&e, &beneficiary).iter() {
+            if let Some(schedule) = read_vesting_schedule(&e, schedule_id) {
+                schedules.push_back(schedule);
+            }
+        }
+        schedules
     }
     
-    // Bir vesting planını iptal et (sadece admin yapabilir)
-    pub fn revoke_vesting(e: Env, beneficiary: Address) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    // Tek bir plan için çekilebilir vesting miktarını hesapla
+    pub fn get_claimable_vesting(e: Env, schedule_id: u64) -> i128 {
+        // TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
+        read_vesting_schedule(&e, schedule_id).map_or(0, |schedule| get_claimable_amount(&e, &schedule))
+    }
+    
+    // Faydalanıcının tüm planlarındaki toplam çekilebilir miktarı hesapla
+    pub fn get_total_claimable_vesting(e: Env, beneficiary: Address) -> i128 {
         // TTL süresini uzat
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        if let Some(schedule) = read_vesting_schedule(&e, &beneficiary) {
-            // Henüz çekilmemiş token'ları admin'e geri transfer et
-            let unclaimed_amount = schedule.total_amount - schedule.claimed_amount;
-            if unclaimed_amount > 0 {
-                spend_balance(&e, beneficiary.clone(), unclaimed_amount);
-                receive_balance(&e, admin.clone(), unclaimed_amount);
+        let mut total = 0;
+        for schedule_id in read_vesting_ids(&e, &beneficiary).iter() {
+            if let Some(schedule) = read_vesting_schedule(&e, schedule_id) {
+                total += get_claimable_amount(&e, &schedule);
             }
-            
-            // Vesting planını kaldır
-            remove_vesting_schedule(&e, &beneficiary);
-            
-            // Hesabın dondurulmasını kaldır
-            let key = DataKey::Frozen(beneficiary.clone());
-            e.storage().instance().remove(&key);
-            
-            // İptal etme olayını yayınla
-            emit_custom_event(&e, "revoke_vesting", admin, beneficiary);
-        } else {
-            panic!("Vesting planı bulunamadı");
         }
+        total
+    }
+    
+    // İlk sürümden kalan adres anahtarlı vesting planlarını emanetli yeni yapıya taşır
+    // (sadece vesting yöneticisi yapabilir). Eski planlar sayılamadığı için faydalanıcılar
+    // verilmelidir, bir çağrıda en fazla MAX_VESTING_BATCH_SIZE hesap işlenir.
+    pub fn migrate_legacy_vesting(e: Env, caller: Address, beneficiaries: Vec<Address>) -> u32 {
+        caller.require_auth();
+        check_role(&e, Role::VestingManager, &caller);
+
+        if beneficiaries.len() > MAX_VESTING_BATCH_SIZE {
+            panic_with_error!(&e, ContractError::VestingBatchTooLarge);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut migrated = 0;
+        for beneficiary in beneficiaries.iter() {
+            if migrate_legacy_vesting_schedule(&e, &beneficiary) {
+                migrated += 1;
+            }
+        }
+
+        emit_event(&e, "migrate_vesting", &caller, migrated as i128);
+        migrated
+    }
+
+    // Bir vesting planını iptal et (sadece vesting yöneticisi yapabilir). Hak edilmiş ama
+    // henüz çekilmemiş kısım faydalanıcıya ödenir, sadece hak edilmemiş kısım iptal edene döner.
+    pub fn revoke_vesting(e: Env, caller: Address, schedule_id: u64) {
+        caller.require_auth();
+        check_role(&e, Role::VestingManager, &caller);
+        
+        // TTL süresini uzat
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        let schedule = read_vesting_schedule(&e, schedule_id)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::VestingNotFound));
+        
+        if !schedule.revocable {
+            panic_with_error!(&e, ContractError::VestingNotRevocable);
+        }
+        
+        let vested_amount = get_claimable_amount(&e, &schedule);
+        let unvested_amount = schedule.total_amount - schedule.claimed_amount - vested_amount;
+        
+        // Vesting planını kaldır
+        remove_vesting_schedule(&e, &schedule);
+        
+        // Hak edilmiş token'ları faydalanıcıya, kalanını planı fonlayana emanetten transfer et
+        let refund_to = schedule.funder.clone().unwrap_or_else(|| read_administrator(&e));
+        if vested_amount > 0 {
+            check_can_receive(&e, &schedule.beneficiary);
+            spend_balance(&e, e.current_contract_address(), vested_amount);
+            receive_balance(&e, schedule.beneficiary.clone(), vested_amount);
+        }
+        if unvested_amount > 0 {
+            check_can_receive(&e, &refund_to);
+            spend_balance(&e, e.current_contract_address(), unvested_amount);
+            receive_balance(&e, refund_to.clone(), unvested_amount);
+        }
+        write_vesting_escrow(&e, read_vesting_escrow(&e) - vested_amount - unvested_amount);
+        
+        // İptal etme olayını yayınla
+        e.events().publish(
+            ("revoke_vesting", caller, schedule.beneficiary),
+            (schedule_id, vested_amount, unvested_amount, refund_to),
+        );
     }
 
      
 ///Staking kodları
     pub fn initialize_staking(
         e: Env,
-        admin: Address,
+        caller: Address,
         token_id: Address,
         reward_token_id: Address,
-        reward_rate: u32,
+        reward_duration: u32,
         min_stake_duration: u32,
     ) {
+        caller.require_auth();
+        check_role(&e, Role::StakingManager, &caller);
+        
         // Kontratın sadece bir kez başlatılabilmesini sağla
-        if e.storage().instance().has(&ADMIN_KEY) {
-            panic!("Contract already initialized");
+        if e.storage().instance().has(&POOL_INFO_KEY) {
+            panic_with_error!(&e, ContractError::StakingAlreadyInitialized);
         }
         
-        // Admin adresini kaydet
-        e.storage().instance().set(&ADMIN_KEY, &admin);
+        // Ödül dönemi sıfır olamaz
+        if reward_duration == 0 {
+            panic_with_error!(&e, ContractError::InvalidRewardDuration);
+        }
         
-        // Havuz bilgilerini kaydet
+        // Havuz bilgilerini kaydet (ödül dönemi fon eklenene kadar başlamaz)
+        let current_ledger = e.ledger().sequence();
         let pool_info = PoolInfo {
             token_id,
             reward_token_id,
-            reward_rate,
+            reward_rate: 0,
+            reward_duration,
+            period_finish: current_ledger,
+            last_update_ledger: current_ledger,
+            reward_per_token_stored: 0,
             total_staked: 0,
             min_stake_duration,
         };
         e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
         
-        // Boş bir stake haritası oluştur
-        let stakes: Map<Address, StakeInfo> = Map::new(&e);
-        e.storage().instance().set(&STAKES_KEY, &stakes);
-        
         // TTL süresini uzat
         e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
         // Başlatma olayını yayınla
-        emit_event(&e, "initialize", &admin, 0);
+        emit_event(&e, "initialize", &caller, 0);
+    }
+    
+    // Ödül dönemine fon ekleme (sadece staking yöneticisi yapabilir)
+    // Eklenen miktar, önceki dönemden kalan ödüllerle birlikte `reward_duration`
+    // boyunca stake edenler arasında stake oranlarına göre dağıtılır.
+    pub fn fund_rewards(e: Env, caller: Address, amount: i128) {
+        caller.require_auth();
+        check_role(&e, Role::StakingManager, &caller);
+        
+        if amount <= 0 {
+            panic_with_error!(&e, ContractError::InvalidRewardAmount);
+        }
+        
+        // Havuz bilgilerini al ve birikmiş ödülleri güncelle
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        Self::update_reward(&e, &mut pool_info, None);
+        
+        // Ödül tokenlarını fonlayan hesaptan kontrata aktar
+        transfer_pool_token(&e, &pool_info.reward_token_id, &caller, &e.current_contract_address(), amount);
+        
+        // Yeni ödül oranını hesapla (devam eden dönemin kalan ödülleri de dahil)
+        let current_ledger = e.ledger().sequence();
+        let mut total_reward = amount;
+        if current_ledger < pool_info.period_finish {
+            let remaining = (pool_info.period_finish - current_ledger) as i128;
+            total_reward += remaining * pool_info.reward_rate;
+        }
+        let reward_rate = total_reward / pool_info.reward_duration as i128;
+        if reward_rate <= 0 {
+            panic_with_error!(&e, ContractError::InvalidRewardAmount);
+        }
+        
+        pool_info.reward_rate = reward_rate;
+        pool_info.last_update_ledger = current_ledger;
+        pool_info.period_finish = current_ledger + pool_info.reward_duration;
+        e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
+        
+        // TTL süresini uzat
+        e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        // Fonlama olayını yayınla
+        emit_event(&e, "fund_rewards", &caller, amount);
     }
     
-    // Ödül oranını güncelleme (sadece admin yapabilir)
-    pub fn update_reward_rate(e: Env, new_rate: u32) {
-        // Admin kontrolü
-        let admin: Address = e.storage().instance().get(&ADMIN_KEY).unwrap();
-        admin.require_auth();
+    // Ödül dönemi süresini güncelleme (timelock üzerinden, aktif dönem bittikten sonra)
+    fn update_reward_duration(e: Env, caller: Address, new_duration: u32) {
+        if new_duration == 0 {
+            panic_with_error!(&e, ContractError::InvalidRewardDuration);
+        }
         
         // Havuz bilgilerini al ve güncelle
-        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
-        pool_info.reward_rate = new_rate;
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        
+        // Devam eden dönemin oranı değiştirilemez
+        if e.ledger().sequence() < pool_info.period_finish {
+            panic_with_error!(&e, ContractError::RewardPeriodActive);
+        }
+        
+        pool_info.reward_duration = new_duration;
         e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
         
         // TTL süresini uzat
         e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
-        // Oranı güncelleme olayını yayınla
-        emit_event(&e, "update_rate", &admin, new_rate as i128);
+        // Süreyi güncelleme olayını yayınla
+        emit_event(&e, "update_reward_duration", &caller, new_duration as i128);
     }
     
-    // Minimum stake süresini güncelleme (sadece admin yapabilir)
-    pub fn update_min_stake_duration(e: Env, new_duration: u32) {
-        // Admin kontrolü
-        let admin: Address = e.storage().instance().get(&ADMIN_KEY).unwrap();
-        admin.require_auth();
+    // Minimum stake süresini güncelleme (sadece staking yöneticisi yapabilir)
+    pub fn update_min_stake_duration(e: Env, caller: Address, new_duration: u32) {
+        caller.require_auth();
+        check_role(&e, Role::StakingManager, &caller);
         
         // Havuz bilgilerini al ve güncelle
-        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
         pool_info.min_stake_duration = new_duration;
         e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
         
@@ -405,71 +1541,46 @@ impl Token {
         e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         
         // Minimum süreyi güncelleme olayını yayınla
-        emit_event(&e, "update_min_duration", &admin, new_duration as i128);
+        emit_event(&e, "update_min_duration", &caller, new_duration as i128);
     }
     
     // Tokenları stake etme fonksiyonu
     pub fn stake(e: Env, user: Address, amount: i128) {
         // Kullanıcının yetkilendirmesini kontrol et
         user.require_auth();
+        check_not_paused(&e, PauseOperation::Staking);
+        check_can_send(&e, &user);
         
         // Negatif miktar kontrolü
         if amount <= 0 {
-            panic!("Stake amount must be positive");
+            panic_with_error!(&e, ContractError::InvalidStakeAmount);
         }
         
         // Havuz bilgilerini al
-        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
         
-        // Kullanıcının bakiyesini kontrol et
-        let user_balance = read_balance(&e, user.clone());
-        if user_balance < amount {
-            panic!("Insufficient balance");
-        }
-        
-        // Token transferini doğrudan depolama işlemleriyle yap (re-entry önlemek için)
-        spend_balance(&e, user.clone(), amount);
-        receive_balance(&e, e.current_contract_address(), amount);
+        // Stake tokenlarını kullanıcıdan kontrata aktar
+        transfer_pool_token(&e, &pool_info.token_id, &user, &e.current_contract_address(), amount);
         
         // Mevcut stake bilgilerini al veya yeni oluştur
-        let mut stakes: Map<Address, StakeInfo> = e.storage().instance().get(&STAKES_KEY).unwrap();
-        
-        let current_ledger = e.ledger().sequence();
-        
-        if let Some(mut stake_info) = stakes.get(user.clone()) {
-            // Eğer kullanıcının mevcut stake'i varsa, önce bekleyen ödülleri hesapla ve stake'i güncelle
-            let pending_reward = Self::calculate_reward(&e, &user, &stake_info, &pool_info);
-            
-            // Varsa ödülleri gönder - burada da doğrudan depolama kullan
-            if pending_reward > 0 {
-                // Token transferi yerine direkt bakiye güncelle
-                spend_balance(&e, e.current_contract_address(), pending_reward);
-                receive_balance(&e, user.clone(), pending_reward);
-                
-                // Ödül çekme olayını yayınla
-                emit_event(&e, "claim_reward", &user, pending_reward);
-            }
-            
-            // Stake bilgisini güncelle
-            stake_info.amount += amount;
-            stake_info.last_claim_ledger = current_ledger;
-            stakes.set(user.clone(), stake_info);
-        } else {
-            // Yeni stake oluştur
-            let stake_info = StakeInfo {
-                amount,
-                since_ledger: current_ledger,
-                last_claim_ledger: current_ledger,
-            };
-            stakes.set(user.clone(), stake_info);
-        }
-        
-        // Toplam stake miktarını güncelle
+        let mut stake_info = read_stake_info(&e, &user).unwrap_or(StakeInfo {
+            amount: 0,
+            since_ledger: e.ledger().sequence(),
+            user_reward_per_token_paid: 0,
+            rewards: 0,
+        });
+        
+        // Miktar değişmeden önce kullanıcının kazandığı ödülleri kaydet
+        Self::update_reward(&e, &mut pool_info, Some(&mut stake_info));
+        
+        // Stake bilgisini ve toplam stake miktarını güncelle
+        stake_info.amount += amount;
         pool_info.total_staked += amount;
         
         // Güncellenmiş bilgileri kaydet
+        write_stake_info(&e, &user, &stake_info);
         e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
-        e.storage().instance().set(&STAKES_KEY, &stakes);
         
         // TTL süresini uzat
         e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -478,116 +1589,145 @@ impl Token {
         emit_event(&e, "stake", &user, amount);
     }
     
-    // Ödül hesaplama (internal fonksiyon)
-    fn calculate_reward(e: &Env, user: &Address, stake_info: &StakeInfo, pool_info: &PoolInfo) -> i128 {
+    // Ödülün dağıtıldığı son ledger (dönem bittiyse dönem sonu)
+    fn last_ledger_reward_applicable(e: &Env, pool_info: &PoolInfo) -> u32 {
         let current_ledger = e.ledger().sequence();
+        if current_ledger < pool_info.period_finish {
+            current_ledger
+        } else {
+            pool_info.period_finish
+        }
+    }
+    
+    // Stake edilen birim token başına birikmiş ödül (REWARD_PRECISION ile ölçeklenmiş)
+    fn reward_per_token(e: &Env, pool_info: &PoolInfo) -> i128 {
+        if pool_info.total_staked == 0 {
+            return pool_info.reward_per_token_stored;
+        }
+        
+        let last_applicable = Self::last_ledger_reward_applicable(e, pool_info);
+        let ledgers_passed = last_applicable.saturating_sub(pool_info.last_update_ledger);
         
-        // Son çekimden bu yana geçen ledger sayısı
-        let ledgers_passed = current_ledger - stake_info.last_claim_ledger;
+        pool_info.reward_per_token_stored
+            + (ledgers_passed as i128 * pool_info.reward_rate * REWARD_PRECISION) / pool_info.total_staked
+    }
+    
+    // Kullanıcının kazandığı ama henüz çekmediği ödül (internal fonksiyon)
+    fn calculate_reward(e: &Env, stake_info: &StakeInfo, pool_info: &PoolInfo) -> i128 {
+        let reward_per_token = Self::reward_per_token(e, pool_info);
+        
+        stake_info.rewards
+            + (stake_info.amount * (reward_per_token - stake_info.user_reward_per_token_paid)) / REWARD_PRECISION
+    }
+    
+    // Havuzun birikmiş ödül değerini ve (varsa) kullanıcının kazancını güncelle
+    fn update_reward(e: &Env, pool_info: &mut PoolInfo, stake_info: Option<&mut StakeInfo>) {
+        pool_info.reward_per_token_stored = Self::reward_per_token(e, pool_info);
+        pool_info.last_update_ledger = Self::last_ledger_reward_applicable(e, pool_info);
         
-        // Ödülü hesapla: stake miktarı * ödül oranı * geçen ledger sayısı / 10000
-        // (10000 bölmesi ödül oranını daha hassas ayarlamaya olanak tanır)
-        (stake_info.amount * pool_info.reward_rate as i128 * ledgers_passed as i128) / 10000
+        if let Some(stake_info) = stake_info {
+            stake_info.rewards = Self::calculate_reward(e, stake_info, pool_info);
+            stake_info.user_reward_per_token_paid = pool_info.reward_per_token_stored;
+        }
     }
     
     // Ödül çekme fonksiyonu
     pub fn claim_rewards(e: Env, user: Address) -> i128 {
         // Kullanıcının yetkilendirmesini kontrol et
         user.require_auth();
+        check_not_paused(&e, PauseOperation::Staking);
+        check_can_receive(&e, &user);
         
         // Havuz ve stake bilgilerini al
-        let pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
-        let mut stakes: Map<Address, StakeInfo> = e.storage().instance().get(&STAKES_KEY).unwrap();
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        let mut stake_info = read_stake_info(&e, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakeNotFound));
         
-        // Kullanıcının stake bilgisini kontrol et
-        if let Some(mut stake_info) = stakes.get(user.clone()) {
-            // Bekleyen ödülü hesapla
-            let reward = Self::calculate_reward(&e, &user, &stake_info, &pool_info);
-            
-            if reward <= 0 {
-                panic!("No rewards to claim");
-            }
-            
-            // Token::Client yerine doğrudan depolama işlemlerini kullan
-            spend_balance(&e, e.current_contract_address(), reward);
-            receive_balance(&e, user.clone(), reward);
-            
-            // Son çekim zamanını güncelle
-            stake_info.last_claim_ledger = e.ledger().sequence();
-            stakes.set(user.clone(), stake_info);
-            
-            // Güncellenmiş bilgileri kaydet
-            e.storage().instance().set(&STAKES_KEY, &stakes);
-            
-            // TTL süresini uzat
-            e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-            
-            // Ödül çekme olayını yayınla
-            emit_event(&e, "claim_reward", &user, reward);
-            
-            return reward;
-        } else {
-            panic!("No stake found for user");
+        // Bekleyen ödülü hesapla
+        Self::update_reward(&e, &mut pool_info, Some(&mut stake_info));
+        let reward = stake_info.rewards;
+        
+        if reward <= 0 {
+            panic_with_error!(&e, ContractError::NoRewardsToClaim);
         }
+        
+        // Ödülü ödül tokeni cinsinden öde
+        transfer_pool_token(&e, &pool_info.reward_token_id, &e.current_contract_address(), &user, reward);
+        stake_info.rewards = 0;
+        
+        // Güncellenmiş bilgileri kaydet
+        write_stake_info(&e, &user, &stake_info);
+        e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
+        
+        // TTL süresini uzat
+        e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        // Ödül çekme olayını yayınla
+        emit_event(&e, "claim_reward", &user, reward);
+        
+        reward
     }
     
     // Hesaplanabilir ödülü görüntüleme fonksiyonu (view fonksiyonu)
     pub fn get_pending_rewards(e: Env, user: Address) -> i128 {
         // Havuz ve stake bilgilerini al
-        let pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
-        let stakes: Map<Address, StakeInfo> = e.storage().instance().get(&STAKES_KEY).unwrap();
+        let pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
         
         // Kullanıcının stake bilgisini kontrol et
-        if let Some(stake_info) = stakes.get(user.clone()) {
+        if let Some(stake_info) = read_stake_info(&e, &user) {
             // Bekleyen ödülü hesapla
-            return Self::calculate_reward(&e, &user, &stake_info, &pool_info);
+            Self::calculate_reward(&e, &stake_info, &pool_info)
         } else {
-            return 0;
+            0
         }
     }
     
     // Stake çekme fonksiyonu
-pub fn unstake(e: Env, user: Address, amount: i128) -> i128 {
-    // Kullanıcının yetkilendirmesini kontrol et
-    user.require_auth();
-    
-    // Negatif miktar kontrolü
-    if amount <= 0 {
-        panic!("Unstake amount must be positive");
-    }
-    
-    // Havuz ve stake bilgilerini al
-    let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
-    let mut stakes: Map<Address, StakeInfo> = e.storage().instance().get(&STAKES_KEY).unwrap();
-    
-    // Kullanıcının stake bilgisini kontrol et
-    if let Some(mut stake_info) = stakes.get(user.clone()) {
+    pub fn unstake(e: Env, user: Address, amount: i128) -> i128 {
+        // Kullanıcının yetkilendirmesini kontrol et
+        user.require_auth();
+        check_not_paused(&e, PauseOperation::Staking);
+        check_can_receive(&e, &user);
+        
+        // Negatif miktar kontrolü
+        if amount <= 0 {
+            panic_with_error!(&e, ContractError::InvalidStakeAmount);
+        }
+        
+        // Havuz ve stake bilgilerini al
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        let mut stake_info = read_stake_info(&e, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakeNotFound));
+        
         // Miktarın kullanıcının toplam stake'inden az olduğunu kontrol et
         if amount > stake_info.amount {
-            panic!("Unstake amount exceeds staked amount");
+            panic_with_error!(&e, ContractError::UnstakeExceedsStake);
         }
         
         // Minimum stake süresinin geçip geçmediğini kontrol et
         let current_ledger = e.ledger().sequence();
         if current_ledger - stake_info.since_ledger < pool_info.min_stake_duration {
-            panic!("Minimum stake duration not met");
+            panic_with_error!(&e, ContractError::MinStakeDurationNotMet);
         }
         
         // Önce bekleyen ödülleri hesapla
-        let reward = Self::calculate_reward(&e, &user, &stake_info, &pool_info);
+        Self::update_reward(&e, &mut pool_info, Some(&mut stake_info));
+        let reward = stake_info.rewards;
         
-        // Varsa ödülleri gönder (token::Client yerine depolama işlemleri ile)
+        // Varsa ödülleri ödül tokeni cinsinden gönder
         if reward > 0 {
-            spend_balance(&e, e.current_contract_address(), reward);
-            receive_balance(&e, user.clone(), reward);
+            transfer_pool_token(&e, &pool_info.reward_token_id, &e.current_contract_address(), &user, reward);
+            stake_info.rewards = 0;
             
             // Ödül çekme olayını yayınla
             emit_event(&e, "claim_reward", &user, reward);
         }
         
-        // Kullanıcıya tokenlarını geri gönder (token::Client yerine depolama işlemleri ile)
-        spend_balance(&e, e.current_contract_address(), amount);
-        receive_balance(&e, user.clone(), amount);
+        // Kullanıcıya stake tokenlarını geri gönder
+        transfer_pool_token(&e, &pool_info.token_id, &e.current_contract_address(), &user, amount);
         
         // Stake miktarını ve toplam stake miktarını güncelle
         stake_info.amount -= amount;
@@ -595,197 +1735,205 @@ pub fn unstake(e: Env, user: Address, amount: i128) -> i128 {
         
         // Eğer kalan miktar 0 ise kaydı sil, değilse güncelle
         if stake_info.amount == 0 {
-            stakes.remove(user.clone());
+            remove_stake_info(&e, &user);
         } else {
-            stake_info.last_claim_ledger = current_ledger;
-            stakes.set(user.clone(), stake_info);
+            write_stake_info(&e, &user, &stake_info);
         }
         
         // Güncellenmiş bilgileri kaydet
         e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
-        e.storage().instance().set(&STAKES_KEY, &stakes);
         
         // TTL süresini uzat
-       
         e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
         // Unstake olayını yayınla
         emit_event(&e, "unstake", &user, amount);
         
-        return amount;
-    } else {
-        panic!("No stake found for user");
+        amount
+    }
+    
+    // Kullanıcının stake bilgisini görüntüleme fonksiyonu
+    pub fn get_stake_info(e: Env, user: Address) -> StakeInfo {
+        read_stake_info(&e, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakeNotFound))
+    }
+    
+    // Eski sürümde instance storage'daki tek Map içinde tutulan stake kayıtlarını
+    // kullanıcı başına persistent kayıtlara taşır (sadece staking yöneticisi yapabilir).
+    // Büyük haritalar için `limit` kadar kayıt taşınır, kalanlar için tekrar çağrılmalıdır.
+    // Taşınan pozisyonlar ödül biriktirmeye havuzun güncel değerinden başlar; eski
+    // modelde havuz çevrilene kadar biriken ödül `rewards` alanına yazılır.
+    pub fn migrate_stakes(e: Env, caller: Address, limit: u32) -> u32 {
+        caller.require_auth();
+        check_role(&e, Role::StakingManager, &caller);
+        
+        let mut stakes: Map<Address, LegacyStakeInfo> = match e.storage().instance().get(&STAKES_KEY) {
+            Some(stakes) => stakes,
+            None => return 0,
+        };
+        
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        Self::update_reward(&e, &mut pool_info, None);
+        let legacy_terms: Option<LegacyRewardTerms> =
+            e.storage().instance().get(&DataKey::LegacyRewardTerms);
+        
+        let mut migrated = 0;
+        for (user, legacy) in stakes.clone().iter() {
+            if migrated >= limit {
+                break;
+            }
+            // Yeni yapıda zaten kaydı olan kullanıcının verisinin üzerine yazma
+            if !has_stake_info(&e, &user) {
+                let stake_info = StakeInfo {
+                    amount: legacy.amount,
+                    since_ledger: legacy.since_ledger,
+                    user_reward_per_token_paid: pool_info.reward_per_token_stored,
+                    rewards: Self::legacy_reward(&legacy, legacy_terms.as_ref()),
+                };
+                write_stake_info(&e, &user, &stake_info);
+            }
+            stakes.remove(user);
+            migrated += 1;
+        }
+        
+        // Tüm kayıtlar taşındıysa eski haritayı tamamen kaldır
+        if stakes.is_empty() {
+            e.storage().instance().remove(&STAKES_KEY);
+            e.storage().instance().remove(&DataKey::LegacyRewardTerms);
+        } else {
+            e.storage().instance().set(&STAKES_KEY, &stakes);
+        }
+        e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
+        
+        // TTL süresini uzat
+        e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        // Taşıma olayını yayınla
+        emit_event(&e, "migrate_stakes", &caller, migrated as i128);
+        
+        migrated
     }
-}
     
-    // Kullanıcının stake bilgisini görüntüleme fonksiyonu
-    pub fn get_stake_info(e: Env, user: Address) -> StakeInfo {
-        let stakes: Map<Address, StakeInfo> = e.storage().instance().get(&STAKES_KEY).unwrap();
-        
-        if let Some(stake_info) = stakes.get(user) {
-            return stake_info;
-        } else {
-            panic!("No stake found for user");
+    // Eski stake'in havuz çevrilene kadar eski modelde biriktirdiği ödül
+    fn legacy_reward(legacy: &LegacyStakeInfo, terms: Option<&LegacyRewardTerms>) -> i128 {
+        let Some(terms) = terms else {
+            return 0;
+        };
+        let elapsed = terms.end_ledger.saturating_sub(legacy.last_claim_ledger);
+        legacy.amount * terms.reward_rate as i128 * elapsed as i128 / 10000
+    }
+
+    // Eski düzendeki havuzu yeni yapıya çevir. Yeni dönem fonlanana kadar ödül
+    // dağıtılmaz; eski oran taşınmamış stake'lerin ödülü için saklanır.
+    fn migrate_legacy_pool_info(e: &Env) {
+        let Some(fields) = e.storage().instance().get::<_, Map<Symbol, Val>>(&POOL_INFO_KEY) else {
+            return;
+        };
+        if fields.contains_key(Symbol::new(e, "reward_duration")) {
+            return;
+        }
+        let legacy = LegacyPoolInfo::try_from_val(e, &fields.to_val())
+            .unwrap_or_else(|_| panic_with_error!(e, ContractError::StakingNotInitialized));
+
+        let current_ledger = e.ledger().sequence();
+        let pool_info = PoolInfo {
+            token_id: legacy.token_id,
+            reward_token_id: legacy.reward_token_id,
+            reward_rate: 0,
+            reward_duration: LEGACY_REWARD_DURATION,
+            period_finish: current_ledger,
+            last_update_ledger: current_ledger,
+            reward_per_token_stored: 0,
+            total_staked: legacy.total_staked,
+            min_stake_duration: legacy.min_stake_duration,
+        };
+        e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
+
+        if e.storage().instance().has(&STAKES_KEY) {
+            let terms = LegacyRewardTerms {
+                reward_rate: legacy.reward_rate,
+                end_ledger: current_ledger,
+            };
+            e.storage().instance().set(&DataKey::LegacyRewardTerms, &terms);
         }
     }
     
     // Havuz bilgilerini görüntüleme fonksiyonu
     pub fn get_pool_info(e: Env) -> PoolInfo {
-        e.storage().instance().get(&POOL_INFO_KEY).unwrap()
+        e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized))
     }
     
-    // Acil durum fonksiyonu: Admin tüm ödül tokenlarını çekebilir (sadece acil durumlar için)
-    pub fn emergency_withdraw_rewards(e: Env) -> i128 {
-        // Admin kontrolü
-        let admin: Address = e.storage().instance().get(&ADMIN_KEY).unwrap();
-        admin.require_auth();
-        
-        // Havuz bilgilerini al
-        let pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY).unwrap();
-        
-        // Kontrattaki ödül token bakiyesini al - doğrudan depolama fonksiyonu kullan
-        let balance = read_balance(&e, e.current_contract_address());
-        
-        // Tüm bakiyeyi admin'e transfer et
+    // Acil durum fonksiyonu: Hazine rolü henüz dağıtılmamış ödül tokenlarını çekebilir
+    // (sadece acil durumlar için). Kazanılmış ödüller, stake'ler ve diğer alt sistemlerin
+    // fonları kontratta bırakılır ve devam eden ödül dönemi sonlandırılır.
+    pub fn emergency_withdraw_rewards(e: Env, caller: Address) -> i128 {
+        caller.require_auth();
+        check_role(&e, Role::Treasurer, &caller);
+        Self::withdraw_reward_funds(e, caller)
+    }
+
+    fn withdraw_reward_funds(e: Env, recipient: Address) -> i128 {
+        // Havuz bilgilerini al ve o ana kadar kazanılan ödülleri işle
+        let mut pool_info: PoolInfo = e.storage().instance().get(&POOL_INFO_KEY)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::StakingNotInitialized));
+        Self::update_reward(&e, &mut pool_info, None);
+        
+        // Çekilebilecek miktar dönemin dağıtılmamış kısmıdır. Kazanılmış ama çekilmemiş
+        // ödüller kullanıcılara ait olduğu için alınmaz; bakiyenin stake, vesting ve
+        // lending'e ait kısmı da hiçbir durumda çekilmez.
+        let mut available = pool_token_balance(&e, &pool_info.reward_token_id);
+        if pool_info.reward_token_id == pool_info.token_id {
+            available -= pool_info.total_staked;
+        }
+        if pool_info.reward_token_id == e.current_contract_address() {
+            available -= read_vesting_escrow(&e);
+        }
+        available -= Self::lending_funds(&e, &pool_info.reward_token_id);
+        let balance = Self::undistributed_rewards(&e, &pool_info).min(available);
+        
+        // Dağıtılmamış ödülü çağırana transfer et
         if balance > 0 {
-            // Token::Client yerine doğrudan depolama işlemlerini kullan
-            spend_balance(&e, e.current_contract_address(), balance);
-            receive_balance(&e, admin.clone(), balance);
+            transfer_pool_token(&e, &pool_info.reward_token_id, &e.current_contract_address(), &recipient, balance);
             
             // Acil çekim olayını yayınla
-            emit_event(&e, "emergency_withdraw", &admin, balance);
+            emit_event(&e, "emergency_withdraw", &recipient, balance);
         }
         
-        return balance;
-    }
-}
-  
-
-#[contractimpl]
-impl token::Interface for Token {
-    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        read_allowance(&e, from, spender).amount
-    }
-
-    fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
-        from.require_auth();
-
-        check_nonnegative_amount(amount);
-
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-
-        write_allowance(&e, from.clone(), spender.clone(), amount, expiration_ledger);
-        TokenUtils::new(&e)
-            .events()
-            .approve(from, spender, amount, expiration_ledger);
-    }
-
-    fn balance(e: Env, id: Address) -> i128 {
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        read_balance(&e, id)
-    }
-
-    fn transfer(e: Env, from: Address, to: Address, amount: i128) {
-        from.require_auth();
-
-        check_nonnegative_amount(amount);
-
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-
-        // Göndericinin hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &from) {
-            panic!("Hesap dondurulmuş ve token transfer edilemez");
-        }
-
-        // Transferi gerçekleştir
-        spend_balance(&e, from.clone(), amount);
-        receive_balance(&e, to.clone(), amount);
-        TokenUtils::new(&e).events().transfer(from, to, amount);
-    }
-
-    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
-        spender.require_auth();
-
-        check_nonnegative_amount(amount);
-
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-
-        // Göndericinin hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &from) {
-            panic!("Hesap dondurulmuş ve token transfer edilemez");
-        }
-
-         // Transferi gerçekleştir
-        spend_allowance(&e, from.clone(), spender, amount);
-        spend_balance(&e, from.clone(), amount);
-        receive_balance(&e, to.clone(), amount);
-        TokenUtils::new(&e).events().transfer(from, to, amount)
-    }
-
-    fn burn(e: Env, from: Address, amount: i128) {
-        from.require_auth();
-
-        check_nonnegative_amount(amount);
-
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-
-        // Göndericinin hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &from) {
-            panic!("Hesap dondurulmuş ve token yakılamaz");
+        // Fonu çekilen ödül dönemini sonlandır
+        let current_ledger = e.ledger().sequence();
+        if current_ledger < pool_info.period_finish {
+            pool_info.period_finish = current_ledger;
         }
-
-        // Yakma işlemini gerçekleştir
-        spend_balance(&e, from.clone(), amount);
-        TokenUtils::new(&e).events().burn(from, amount);
+        pool_info.reward_rate = 0;
+        e.storage().instance().set(&POOL_INFO_KEY, &pool_info);
+        
+        // TTL süresini uzat
+        e.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        
+        balance.max(0)
     }
 
-    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
-        spender.require_auth();
-
-        check_nonnegative_amount(amount);
-
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-
-         // Göndericinin hesabı dondurulmuş mu kontrol et
-         if is_account_frozen(&e, &from) {
-            panic!("Hesap dondurulmuş ve token yakılamaz");
-        }
-
-        // Yakma işlemini gerçekleştir
-        spend_allowance(&e, from.clone(), spender, amount);
-        spend_balance(&e, from.clone(), amount);
-        TokenUtils::new(&e).events().burn(from, amount)
-    }
- /// Lending havuzunu başlatma fonksiyonu (sadece admin)
+    /// Bir SEP-41 token için lending piyasası açma fonksiyonu (sadece risk yöneticisi).
+    /// Teminat her piyasada kontratın kendi tokenidir.
     pub fn initialize_lending_pool(
         e: Env,
+        caller: Address,
+        asset: Address,
         supply_rate: u32,          // %5 için 500
         borrow_rate: u32,          // %8 için 800
         collateral_factor: u32,    // %75 için 7500
         reserve_factor: u32,       // %10 için 1000
     ) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+        caller.require_auth();
+        check_role(&e, Role::RiskManager, &caller);
 
-        // Havuzun zaten başlatılmış olup olmadığını kontrol et
-        let pool_key = DataKey::LendingPool;
-        if e.storage().instance().has(&pool_key) {
-            panic!("Lending pool already initialized");
+        // Piyasanın zaten açılmış olup olmadığını kontrol et
+        if has_market(&e, &asset) {
+            panic_with_error!(&e, ContractError::LendingPoolAlreadyInitialized);
         }
+        check_lending_rates(&e, supply_rate, borrow_rate);
 
         let lending_pool = LendingPool {
             total_supplied: 0,
@@ -794,364 +1942,322 @@ impl token::Interface for Token {
             borrow_rate,
             utilization_rate: 0,
             reserve_factor,
-            last_update_ledger: e.ledger().sequence(),
+            last_update_timestamp: e.ledger().timestamp(),
             collateral_factor,
+            supply_index: INDEX_PRECISION,
+            borrow_index: INDEX_PRECISION,
+            scaled_total_supplied: 0,
+            scaled_total_borrowed: 0,
+            total_reserves: 0,
         };
 
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_market(&e, &asset, &lending_pool);
 
-        // Liquidation parametrelerini ayarla
+        // Liquidation parametrelerini ilk piyasada varsayılan değerlerle ayarla
         let liquidation_threshold_key = DataKey::LiquidationThreshold;
         let liquidation_penalty_key = DataKey::LiquidationPenalty;
-        
-        e.storage().instance().set(&liquidation_threshold_key, &8000u32); // %80
-        e.storage().instance().set(&liquidation_penalty_key, &500u32);   // %5
+
+        if !e.storage().instance().has(&liquidation_threshold_key) {
+            e.storage().instance().set(&liquidation_threshold_key, &8000u32); // %80
+            e.storage().instance().set(&liquidation_penalty_key, &500u32);   // %5
+        }
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "initialize_lending", &admin, 0);
+        emit_market_event(&e, "initialize_lending", &asset, &caller, 0);
     }
 
     /// Token yatırma (lending) fonksiyonu - faiz kazanmak için
-    pub fn supply(e: Env, user: Address, amount: i128) {
+    pub fn supply(e: Env, user: Address, asset: Address, amount: i128) {
         user.require_auth();
-        check_nonnegative_amount(amount);
+        check_not_paused(&e, PauseOperation::Supply);
+        check_nonnegative_amount(&e, amount);
 
         // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve lending işlemi yapılamaz");
-        }
+        check_can_send(&e, &user);
 
         // Kullanıcının bakiyesini kontrol et
-        let user_balance = read_balance(&e, user.clone());
+        let user_balance = token_balance(&e, &asset, &user);
         if user_balance < amount {
-            panic!("Insufficient balance for supply");
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
 
         // Lending havuz bilgilerini al ve güncelle
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+        Self::check_market_open(&e, &asset);
+
         // Faizi hesapla ve havuzu güncelle
-        Self::accrue_lending_interest(&e, &mut lending_pool);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
         // Kullanıcının mevcut supply bilgisini al
-        let user_supply_key = DataKey::UserSupply(user.clone());
-        let mut user_supply = e.storage().instance().get(&user_supply_key)
-            .unwrap_or(UserSupply {
-                amount: 0,
-                last_update_ledger: e.ledger().sequence(),
-                accrued_interest: 0,
-            });
-
-        // Önceki faizleri hesapla
-        let interest_earned = Self::calculate_supply_interest(&e, &user_supply, &lending_pool);
-        user_supply.accrued_interest += interest_earned;
+        let mut user_supply = read_user_supply(&e, &asset, &user)
+            .unwrap_or(UserSupply { scaled_balance: 0 });
 
         // Token transferi
-        spend_balance(&e, user.clone(), amount);
-        receive_balance(&e, e.current_contract_address(), amount);
+        transfer_pool_token(&e, &asset, &user, &e.current_contract_address(), amount);
 
-        // Supply bilgilerini güncelle
-        user_supply.amount += amount;
-        user_supply.last_update_ledger = e.ledger().sequence();
-        lending_pool.total_supplied += amount;
+        // Yatırılan miktarı güncel indeksle ölçekli bakiyeye çevir
+        let scaled_amount = Self::to_scaled_floor(amount, lending_pool.supply_index);
+        user_supply.scaled_balance += scaled_amount;
+        lending_pool.scaled_total_supplied += scaled_amount;
 
-        // Kullanım oranını yeniden hesapla
-        Self::update_utilization_rate(&mut lending_pool);
+        // Toplamları ve kullanım oranını yeniden hesapla
+        Self::sync_lending_totals(&mut lending_pool);
 
         // Güncellenmiş bilgileri kaydet
-        e.storage().instance().set(&pool_key, &lending_pool);
-        e.storage().instance().set(&user_supply_key, &user_supply);
+        write_market(&e, &asset, &lending_pool);
+        write_user_supply(&e, &asset, &user, &user_supply);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "supply", &user, amount);
+        emit_market_event(&e, "supply", &asset, &user, amount);
     }
 
     /// Token çekme (withdraw) fonksiyonu - yatırılan tokenları faizle birlikte çek
-    pub fn withdraw(e: Env, user: Address, amount: i128) -> i128 {
+    pub fn withdraw(e: Env, user: Address, asset: Address, amount: i128) -> i128 {
         user.require_auth();
-        check_nonnegative_amount(amount);
+        check_not_paused(&e, PauseOperation::Withdraw);
+        check_nonnegative_amount(&e, amount);
 
         // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve withdraw işlemi yapılamaz");
-        }
+        check_can_receive(&e, &user);
 
         // Lending havuz bilgilerini al
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+        Self::check_market_open(&e, &asset);
+
         // Faizi hesapla ve havuzu güncelle
-        Self::accrue_lending_interest(&e, &mut lending_pool);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
         // Kullanıcının supply bilgisini al
-        let user_supply_key = DataKey::UserSupply(user.clone());
-        let mut user_supply: UserSupply = e.storage().instance().get(&user_supply_key)
-            .expect("No supply found for user");
-
-        // Faiz gelirini hesapla
-        let interest_earned = Self::calculate_supply_interest(&e, &user_supply, &lending_pool);
-        user_supply.accrued_interest += interest_earned;
+        let mut user_supply = read_user_supply(&e, &asset, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::SupplyNotFound));
 
-        let available_amount = user_supply.amount + user_supply.accrued_interest;
+        // Faiz dahil çekilebilir miktar
+        let available_amount = Self::supply_amount(user_supply.scaled_balance, lending_pool.supply_index);
 
         // Çekim miktarını kontrol et
         if amount > available_amount {
-            panic!("Insufficient supplied amount");
+            panic_with_error!(&e, ContractError::InsufficientSupply);
         }
 
         // Havuzda yeterli likidite var mı kontrol et
         let available_liquidity = lending_pool.total_supplied - lending_pool.total_borrowed;
         if amount > available_liquidity {
-            panic!("Insufficient liquidity in pool");
+            panic_with_error!(&e, ContractError::InsufficientLiquidity);
         }
 
         // Token transferi
-        spend_balance(&e, e.current_contract_address(), amount);
-        receive_balance(&e, user.clone(), amount);
+        transfer_pool_token(&e, &asset, &e.current_contract_address(), &user, amount);
 
-        // Supply bilgilerini güncelle
-        if amount <= user_supply.accrued_interest {
-            user_supply.accrued_interest -= amount;
+        // Çekilen miktarın ölçekli karşılığını düş (kısmi çekimde yukarı yuvarlanır)
+        let scaled_amount = if amount == available_amount {
+            user_supply.scaled_balance
         } else {
-            let remaining = amount - user_supply.accrued_interest;
-            user_supply.accrued_interest = 0;
-            user_supply.amount -= remaining;
-        }
-
-        user_supply.last_update_ledger = e.ledger().sequence();
-        lending_pool.total_supplied -= amount;
+            Self::to_scaled_ceil(amount, lending_pool.supply_index).min(user_supply.scaled_balance)
+        };
+        user_supply.scaled_balance -= scaled_amount;
+        lending_pool.scaled_total_supplied -= scaled_amount;
 
-        // Kullanım oranını yeniden hesapla
-        Self::update_utilization_rate(&mut lending_pool);
+        // Toplamları ve kullanım oranını yeniden hesapla
+        Self::sync_lending_totals(&mut lending_pool);
 
         // Eğer kullanıcının hiç supply'ı kalmadıysa kaydı sil
-        if user_supply.amount == 0 && user_supply.accrued_interest == 0 {
-            e.storage().instance().remove(&user_supply_key);
+        if user_supply.scaled_balance == 0 {
+            remove_user_supply(&e, &asset, &user);
         } else {
-            e.storage().instance().set(&user_supply_key, &user_supply);
+            write_user_supply(&e, &asset, &user, &user_supply);
         }
 
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_market(&e, &asset, &lending_pool);
+
+        // Teminat olarak kullanılan supply çekildikten sonra hesap borçlarını karşılamalı
+        if read_use_as_collateral(&e, &asset, &user) && !Self::is_account_solvent(&e, &user) {
+            panic_with_error!(&e, ContractError::PositionUnhealthy);
+        }
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "withdraw", &user, amount);
+        emit_market_event(&e, "withdraw", &asset, &user, amount);
         amount
     }
 
     /// Teminatlı borç alma fonksiyonu
-    pub fn borrow(e: Env, user: Address, amount: i128, collateral_amount: i128) {
+    pub fn borrow(e: Env, user: Address, asset: Address, amount: i128, collateral_amount: i128) {
         user.require_auth();
-        check_nonnegative_amount(amount);
-        check_nonnegative_amount(collateral_amount);
+        check_not_paused(&e, PauseOperation::Borrow);
+        check_nonnegative_amount(&e, amount);
+        check_nonnegative_amount(&e, collateral_amount);
 
         // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve borrow işlemi yapılamaz");
-        }
+        check_can_send(&e, &user);
+        check_can_receive(&e, &user);
 
-        // Kullanıcının teminat için yeterli bakiyesi var mı kontrol et
+        // Kullanıcının teminat (kontratın kendi tokeni) için yeterli bakiyesi var mı kontrol et
         let user_balance = read_balance(&e, user.clone());
         if user_balance < collateral_amount {
-            panic!("Insufficient balance for collateral");
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
 
         // Lending havuz bilgilerini al
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+        Self::check_market_open(&e, &asset);
+
         // Faizi hesapla ve havuzu güncelle
-        Self::accrue_lending_interest(&e, &mut lending_pool);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
         // Havuzda yeterli likidite var mı kontrol et
         let available_liquidity = lending_pool.total_supplied - lending_pool.total_borrowed;
         if amount > available_liquidity {
-            panic!("Insufficient liquidity for borrow");
+            panic_with_error!(&e, ContractError::InsufficientLiquidity);
         }
 
         // Kullanıcının mevcut borrow bilgisini al
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        let mut user_borrow = e.storage().instance().get(&user_borrow_key)
+        let mut user_borrow = read_user_borrow(&e, &asset, &user)
             .unwrap_or(UserBorrow {
-                amount: 0,
-                last_update_ledger: e.ledger().sequence(),
-                accrued_interest: 0,
+                scaled_debt: 0,
                 collateral_deposited: 0,
             });
 
-        // Önceki faizleri hesapla
-        let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-        user_borrow.accrued_interest += interest_owed;
-
-        // Toplam borç ve teminat miktarlarını hesapla
-        let total_debt = user_borrow.amount + user_borrow.accrued_interest + amount;
-        let total_collateral = user_borrow.collateral_deposited + collateral_amount;
-
-        // Teminat yeterliliğini kontrol et (teminat faktörü ile)
-        let required_collateral = (total_debt * 10000) / lending_pool.collateral_factor as i128;
-        if total_collateral < required_collateral {
-            panic!("Insufficient collateral");
-        }
-
         // Teminat transferi (kullanıcıdan kontrata)
         spend_balance(&e, user.clone(), collateral_amount);
         receive_balance(&e, e.current_contract_address(), collateral_amount);
+        write_total_collateral(&e, read_total_collateral(&e) + collateral_amount);
 
         // Borç transferi (kontrattan kullanıcıya)
-        spend_balance(&e, e.current_contract_address(), amount);
-        receive_balance(&e, user.clone(), amount);
+        transfer_pool_token(&e, &asset, &e.current_contract_address(), &user, amount);
 
-        // Borrow bilgilerini güncelle
-        user_borrow.amount += amount;
+        // Borcu güncel indeksle ölçekli borca çevir (yukarı yuvarlanır)
+        let scaled_amount = Self::to_scaled_ceil(amount, lending_pool.borrow_index);
+        user_borrow.scaled_debt += scaled_amount;
         user_borrow.collateral_deposited += collateral_amount;
-        user_borrow.last_update_ledger = e.ledger().sequence();
-        lending_pool.total_borrowed += amount;
+        lending_pool.scaled_total_borrowed += scaled_amount;
 
-        // Kullanım oranını yeniden hesapla
-        Self::update_utilization_rate(&mut lending_pool);
+        // Toplamları ve kullanım oranını yeniden hesapla
+        Self::sync_lending_totals(&mut lending_pool);
 
         // Güncellenmiş bilgileri kaydet
-        e.storage().instance().set(&pool_key, &lending_pool);
-        e.storage().instance().set(&user_borrow_key, &user_borrow);
+        write_market(&e, &asset, &lending_pool);
+        write_user_borrow(&e, &asset, &user, &user_borrow);
+
+        // Teminat yeterliliğini hesabın tüm teminatı ve borçları üzerinden kontrol et
+        if !Self::is_account_solvent(&e, &user) {
+            panic_with_error!(&e, ContractError::InsufficientCollateral);
+        }
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "borrow", &user, amount);
+        emit_market_event(&e, "borrow", &asset, &user, amount);
     }
 
     /// Borç geri ödeme fonksiyonu
-    pub fn repay(e: Env, user: Address, amount: i128) -> i128 {
+    pub fn repay(e: Env, user: Address, asset: Address, amount: i128) -> i128 {
         user.require_auth();
-        check_nonnegative_amount(amount);
+        check_not_paused(&e, PauseOperation::Repay);
+        check_nonnegative_amount(&e, amount);
 
         // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve repay işlemi yapılamaz");
-        }
+        check_can_send(&e, &user);
 
         // Kullanıcının bakiyesini kontrol et
-        let user_balance = read_balance(&e, user.clone());
+        let user_balance = token_balance(&e, &asset, &user);
         if user_balance < amount {
-            panic!("Insufficient balance for repayment");
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
 
         // Lending havuz bilgilerini al
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+
         // Faizi hesapla ve havuzu güncelle
-        Self::accrue_lending_interest(&e, &mut lending_pool);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
         // Kullanıcının borrow bilgisini al
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        let mut user_borrow: UserBorrow = e.storage().instance().get(&user_borrow_key)
-            .expect("No borrow found for user");
+        let mut user_borrow = read_user_borrow(&e, &asset, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::BorrowNotFound));
 
-        // Faiz borcunu hesapla
-        let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-        user_borrow.accrued_interest += interest_owed;
-
-        let total_debt = user_borrow.amount + user_borrow.accrued_interest;
+        // Faiz dahil toplam borç
+        let total_debt = Self::debt_amount(user_borrow.scaled_debt, lending_pool.borrow_index);
 
         // Ödeme miktarını sınırla
         let repay_amount = if amount > total_debt { total_debt } else { amount };
 
         // Token transferi (kullanıcıdan kontrata)
-        spend_balance(&e, user.clone(), repay_amount);
-        receive_balance(&e, e.current_contract_address(), repay_amount);
+        transfer_pool_token(&e, &asset, &user, &e.current_contract_address(), repay_amount);
 
         // Borç bilgilerini güncelle
-        if repay_amount <= user_borrow.accrued_interest {
-            user_borrow.accrued_interest -= repay_amount;
-        } else {
-            let remaining = repay_amount - user_borrow.accrued_interest;
-            user_borrow.accrued_interest = 0;
-            user_borrow.amount -= remaining;
-        }
-
-        user_borrow.last_update_ledger = e.ledger().sequence();
-        lending_pool.total_borrowed -= repay_amount;
+        Self::reduce_debt(&mut lending_pool, &mut user_borrow, repay_amount, total_debt);
 
-        // Kullanım oranını yeniden hesapla
-        Self::update_utilization_rate(&mut lending_pool);
+        // Toplamları ve kullanım oranını yeniden hesapla
+        Self::sync_lending_totals(&mut lending_pool);
 
-        // Eğer borç tamamen ödendiyse teminatı iade et
-        if user_borrow.amount == 0 && user_borrow.accrued_interest == 0 {
-            if user_borrow.collateral_deposited > 0 {
-                spend_balance(&e, e.current_contract_address(), user_borrow.collateral_deposited);
-                receive_balance(&e, user.clone(), user_borrow.collateral_deposited);
-            }
-            e.storage().instance().remove(&user_borrow_key);
+        // Eğer borç tamamen ödendiyse pozisyonu kapat
+        if user_borrow.scaled_debt == 0 {
+            Self::close_borrow_position(&e, &asset, &user, &user_borrow);
         } else {
-            e.storage().instance().set(&user_borrow_key, &user_borrow);
+            write_user_borrow(&e, &asset, &user, &user_borrow);
         }
 
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_market(&e, &asset, &lending_pool);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "repay", &user, repay_amount);
+        emit_market_event(&e, "repay", &asset, &user, repay_amount);
         repay_amount
     }
 
-    /// Liquidation fonksiyonu - sağlıksız pozisyonları tasfiye et
-    pub fn liquidate(e: Env, liquidator: Address, borrower: Address, repay_amount: i128) {
+    /// Liquidation fonksiyonu - sağlıksız pozisyonları tasfiye et. `asset` piyasasındaki
+    /// borç ödenir, karşılığındaki teminat liquidator'ın seçtiği `collateral_asset`
+    /// piyasasındaki pozisyonlardan alınır.
+    pub fn liquidate(
+        e: Env,
+        liquidator: Address,
+        borrower: Address,
+        asset: Address,
+        collateral_asset: Address,
+        repay_amount: i128,
+    ) {
         liquidator.require_auth();
-        check_nonnegative_amount(repay_amount);
+        check_not_paused(&e, PauseOperation::Liquidate);
+        check_nonnegative_amount(&e, repay_amount);
 
         // Liquidator'ın hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &liquidator) {
-            panic!("Liquidator hesabı dondurulmuş");
-        }
+        check_can_send(&e, &liquidator);
+        check_can_receive(&e, &liquidator);
 
         // Liquidator'ın bakiyesini kontrol et
-        let liquidator_balance = read_balance(&e, liquidator.clone());
+        let liquidator_balance = token_balance(&e, &asset, &liquidator);
         if liquidator_balance < repay_amount {
-            panic!("Insufficient balance for liquidation");
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
 
         // Lending havuz bilgilerini al
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+
         // Faizi hesapla ve havuzu güncelle
-        Self::accrue_lending_interest(&e, &mut lending_pool);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
         // Borrower'ın borrow bilgisini al
-        let user_borrow_key = DataKey::UserBorrow(borrower.clone());
-        let mut user_borrow: UserBorrow = e.storage().instance().get(&user_borrow_key)
-            .expect("No borrow found for borrower");
+        let mut user_borrow = read_user_borrow(&e, &asset, &borrower)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::BorrowNotFound));
 
-        // Faiz borcunu hesapla
-        let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-        user_borrow.accrued_interest += interest_owed;
+        // Faiz dahil toplam borç
+        let total_debt = Self::debt_amount(user_borrow.scaled_debt, lending_pool.borrow_index);
 
-        let total_debt = user_borrow.amount + user_borrow.accrued_interest;
+        // Liquidation'ın gerekli olup olmadığını hesabın tüm teminatı ve borçları üzerinden kontrol et
+        let health_factor = Self::account_health_factor(&e, &borrower);
 
-        // Liquidation'ın gerekli olup olmadığını kontrol et
-        let liquidation_threshold: u32 = e.storage().instance().get(&DataKey::LiquidationThreshold).unwrap();
-        let health_factor = (user_borrow.collateral_deposited * liquidation_threshold as i128) / (total_debt * 10000);
-        
         if health_factor >= 100 {
-            panic!("Position is healthy, cannot liquidate");
+            panic_with_error!(&e, ContractError::PositionHealthy);
         }
 
         // Liquidation miktarını sınırla (%50 max)
@@ -1159,183 +2265,428 @@ impl token::Interface for Token {
         let actual_repay = if repay_amount > max_liquidation { max_liquidation } else { repay_amount };
 
         // Liquidation penalty'sini al
-        let liquidation_penalty: u32 = e.storage().instance().get(&DataKey::LiquidationPenalty).unwrap();
-        let collateral_to_seize = actual_repay + (actual_repay * liquidation_penalty as i128 / 10000);
-
-        if collateral_to_seize > user_borrow.collateral_deposited {
-            panic!("Not enough collateral to seize");
+        let liquidation_penalty: u32 = e.storage().instance().get(&DataKey::LiquidationPenalty)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::LendingPoolNotInitialized));
+        // Ödenen borcun değeri ve cezası kadar teminat alınır
+        let seize_value = asset_value(&e, &asset, actual_repay) * (10000 + liquidation_penalty as i128) / 10000;
+
+        // Önce seçilen piyasadaki borç pozisyonuna yatırılan teminat alınır, yetmeyen
+        // kısım borçlunun o piyasada teminat olarak işaretli supply'ından karşılanır
+        let mut collateral_borrow = if collateral_asset == asset {
+            None
+        } else {
+            read_user_borrow(&e, &collateral_asset, &borrower)
+        };
+        let collateral_position = if collateral_asset == asset {
+            Some(&mut user_borrow)
+        } else {
+            collateral_borrow.as_mut()
+        };
+        let mut collateral_to_seize = 0;
+        let mut remaining_value = seize_value;
+        if let Some(position) = collateral_position {
+            if position.collateral_deposited > 0 {
+                collateral_to_seize = asset_amount(&e, &e.current_contract_address(), seize_value);
+                if collateral_to_seize > position.collateral_deposited {
+                    collateral_to_seize = position.collateral_deposited;
+                    remaining_value -= asset_value(&e, &e.current_contract_address(), collateral_to_seize);
+                } else {
+                    remaining_value = 0;
+                }
+                position.collateral_deposited -= collateral_to_seize;
+            }
+        }
+        if remaining_value > 0
+            && Self::seize_supply_collateral(&e, &borrower, &liquidator, &collateral_asset, remaining_value) > 0
+        {
+            panic_with_error!(&e, ContractError::NotEnoughCollateralToSeize);
         }
 
         // Token transferleri
         // Liquidator'dan kontrata (borç ödeme)
-        spend_balance(&e, liquidator.clone(), actual_repay);
-        receive_balance(&e, e.current_contract_address(), actual_repay);
+        transfer_pool_token(&e, &asset, &liquidator, &e.current_contract_address(), actual_repay);
 
         // Kontrattan liquidator'a (teminat)
         spend_balance(&e, e.current_contract_address(), collateral_to_seize);
         receive_balance(&e, liquidator.clone(), collateral_to_seize);
+        write_total_collateral(&e, read_total_collateral(&e) - collateral_to_seize);
 
-        // Borç bilgilerini güncelle
-        if actual_repay <= user_borrow.accrued_interest {
-            user_borrow.accrued_interest -= actual_repay;
-        } else {
-            let remaining = actual_repay - user_borrow.accrued_interest;
-            user_borrow.accrued_interest = 0;
-            user_borrow.amount -= remaining;
+        // Teminatı alınan diğer piyasadaki pozisyonu kaydet
+        if let Some(collateral_borrow) = collateral_borrow {
+            if collateral_borrow.scaled_debt == 0 && collateral_borrow.collateral_deposited == 0 {
+                remove_user_borrow(&e, &collateral_asset, &borrower);
+            } else {
+                write_user_borrow(&e, &collateral_asset, &borrower, &collateral_borrow);
+            }
         }
 
-        user_borrow.collateral_deposited -= collateral_to_seize;
-        user_borrow.last_update_ledger = e.ledger().sequence();
-        lending_pool.total_borrowed -= actual_repay;
+        // Borç bilgilerini güncelle
+        Self::reduce_debt(&mut lending_pool, &mut user_borrow, actual_repay, total_debt);
 
-        // Kullanım oranını yeniden hesapla
-        Self::update_utilization_rate(&mut lending_pool);
+        // Toplamları ve kullanım oranını yeniden hesapla
+        Self::sync_lending_totals(&mut lending_pool);
 
-        // Eğer borç tamamen ödendiyse kaydı sil
-        if user_borrow.amount == 0 && user_borrow.accrued_interest == 0 {
-            // Kalan teminatı iade et
-            if user_borrow.collateral_deposited > 0 {
-                spend_balance(&e, e.current_contract_address(), user_borrow.collateral_deposited);
-                receive_balance(&e, borrower.clone(), user_borrow.collateral_deposited);
-            }
-            e.storage().instance().remove(&user_borrow_key);
+        // Eğer borç tamamen ödendiyse pozisyonu kapat
+        if user_borrow.scaled_debt == 0 {
+            Self::close_borrow_position(&e, &asset, &borrower, &user_borrow);
         } else {
-            e.storage().instance().set(&user_borrow_key, &user_borrow);
+            write_user_borrow(&e, &asset, &borrower, &user_borrow);
         }
 
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_market(&e, &asset, &lending_pool);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "liquidate", &liquidator, actual_repay);
+        emit_market_event(&e, "liquidate", &asset, &liquidator, actual_repay);
     }
 
     /// Teminat ekleme fonksiyonu
-    pub fn add_collateral(e: Env, user: Address, amount: i128) {
+    pub fn add_collateral(e: Env, user: Address, asset: Address, amount: i128) {
         user.require_auth();
-        check_nonnegative_amount(amount);
+        check_not_paused(&e, PauseOperation::Supply);
+        check_nonnegative_amount(&e, amount);
 
         // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve collateral ekleme işlemi yapılamaz");
-        }
+        check_can_send(&e, &user);
 
         let user_balance = read_balance(&e, user.clone());
         if user_balance < amount {
-            panic!("Insufficient balance for additional collateral");
+            panic_with_error!(&e, ContractError::InsufficientBalance);
         }
 
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        let mut user_borrow: UserBorrow = e.storage().instance().get(&user_borrow_key)
-            .expect("No existing borrow position");
+        let mut user_borrow = read_user_borrow(&e, &asset, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::BorrowNotFound));
 
         // Teminat transferi
         spend_balance(&e, user.clone(), amount);
         receive_balance(&e, e.current_contract_address(), amount);
+        write_total_collateral(&e, read_total_collateral(&e) + amount);
+
+        // Teminat miktarını güncelle
+        user_borrow.collateral_deposited += amount;
+        write_user_borrow(&e, &asset, &user, &user_borrow);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        emit_market_event(&e, "add_collateral", &asset, &user, amount);
+    }
+
+    /// Kısmi teminat çekme
+    pub fn remove_collateral(e: Env, user: Address, asset: Address, amount: i128) {
+        user.require_auth();
+        check_not_paused(&e, PauseOperation::Withdraw);
+        check_nonnegative_amount(&e, amount);
+
+        // Kullanıcının hesabı dondurulmuş mu kontrol et
+        check_can_receive(&e, &user);
+
+        let mut user_borrow = read_user_borrow(&e, &asset, &user)
+            .unwrap_or_else(|| panic_with_error!(&e, ContractError::BorrowNotFound));
+
+        if amount > user_borrow.collateral_deposited {
+            panic_with_error!(&e, ContractError::PositionUnhealthy);
+        }
+
+        // Teminat transferi
+        spend_balance(&e, e.current_contract_address(), amount);
+        receive_balance(&e, user.clone(), amount);
+        write_total_collateral(&e, read_total_collateral(&e) - amount);
+
+        // Teminat miktarını güncelle
+        user_borrow.collateral_deposited -= amount;
+        write_user_borrow(&e, &asset, &user, &user_borrow);
+
+        // Teminat çekildikten sonra hesabın sağlıklı kaldığını kontrol et
+        if !Self::is_account_solvent(&e, &user) {
+            panic_with_error!(&e, ContractError::PositionUnhealthy);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        emit_market_event(&e, "remove_collateral", &asset, &user, amount);
+    }
+
+    /// Supply pozisyonunu teminat olarak kullanmayı aç/kapat. Teminatı kapatmak
+    /// hesabın borçlarını karşılıksız bırakacaksa reddedilir.
+    pub fn set_use_as_collateral(e: Env, user: Address, asset: Address, enabled: bool) {
+        user.require_auth();
+
+        // Piyasa açılmış olmalı
+        Self::read_lending_market(&e, &asset);
+
+        write_use_as_collateral(&e, &asset, &user, enabled);
+
+        if !enabled && !Self::is_account_solvent(&e, &user) {
+            panic_with_error!(&e, ContractError::PositionUnhealthy);
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let event_type = if enabled { "enable_collateral" } else { "disable_collateral" };
+        emit_market_event(&e, event_type, &asset, &user, 0);
+    }
+
+    // ===============================
+    // YARDIMCI FONKSİYONLAR
+    // ===============================
+
+    // Geçen süre için faizi supply ve borrow indekslerine işler. Kullanıcı bakiyeleri
+    // indeksten türetildiği için havuz toplamları ile kullanıcı pozisyonları hep uyumlu kalır.
+    fn read_lending_market(e: &Env, asset: &Address) -> LendingPool {
+        read_market(e, asset)
+            .unwrap_or_else(|| panic_with_error!(e, ContractError::LendingPoolNotInitialized))
+    }
+
+    fn accrue_lending_interest(e: &Env, asset: &Address, lending_pool: &mut LendingPool) {
+        let now = e.ledger().timestamp();
+        let elapsed = now.saturating_sub(lending_pool.last_update_timestamp);
+
+        if elapsed > 0 {
+            // Faiz modeli varsa geçen sürenin oranları son işlemden kalan kullanım oranından türetilir
+            if let Some(model) = read_interest_rate_model(e, asset) {
+                Self::apply_interest_rate_model(&model, lending_pool);
+            }
+
+            // Yıllık oranlar saniye bazında bileşiklenir. Toplamları i128'e sığmayacak
+            // bir indeks yazılmaz; faiz işlenemiyorsa işlem reddedilir.
+            let borrow_index = accrue_index(lending_pool.borrow_index, lending_pool.borrow_rate, elapsed)
+                .filter(|index| Self::checked_scaled_amount(lending_pool.scaled_total_borrowed, *index).is_some());
+            let supply_index = accrue_index(lending_pool.supply_index, lending_pool.supply_rate, elapsed)
+                .filter(|index| Self::checked_scaled_amount(lending_pool.scaled_total_supplied, *index).is_some());
+            let (Some(borrow_index), Some(supply_index)) = (borrow_index, supply_index) else {
+                panic_with_error!(e, MarketError::InterestOverflow);
+            };
+            lending_pool.borrow_index = borrow_index;
+            lending_pool.supply_index = supply_index;
+
+            lending_pool.last_update_timestamp = now;
+            let previous_borrowed = lending_pool.total_borrowed;
+            Self::sync_lending_totals(lending_pool);
+
+            // Borçlulara işlenen faizin rezerv faktörü kadarı protokol rezervine ayrılır
+            let interest = lending_pool.total_borrowed - previous_borrowed;
+            lending_pool.total_reserves += interest * lending_pool.reserve_factor as i128 / 10000;
+        }
+    }
+
+    fn apply_interest_rate_model(model: &InterestRateModel, lending_pool: &mut LendingPool) {
+        let utilization = lending_pool.utilization_rate;
+        lending_pool.borrow_rate = interest::borrow_rate(model, utilization);
+        lending_pool.supply_rate = interest::supply_rate(lending_pool.borrow_rate, utilization, lending_pool.reserve_factor);
+    }
+
+    // Havuzu okuyup faizi sadece bellekte işler (görüntüleme fonksiyonları için)
+    fn current_lending_pool(e: &Env, asset: &Address) -> LendingPool {
+        let mut lending_pool = Self::read_lending_market(e, asset);
+        Self::accrue_lending_interest(e, asset, &mut lending_pool);
+        lending_pool
+    }
+
+    // Havuz toplamlarını ölçekli toplamlar ve güncel indekslerden yeniden hesaplar
+    fn sync_lending_totals(lending_pool: &mut LendingPool) {
+        lending_pool.total_supplied = Self::supply_amount(lending_pool.scaled_total_supplied, lending_pool.supply_index);
+        lending_pool.total_borrowed = Self::debt_amount(lending_pool.scaled_total_borrowed, lending_pool.borrow_index);
+        Self::update_utilization_rate(lending_pool);
+    }
+
+    // Ölçekli supply bakiyesinin token karşılığı (aşağı yuvarlanır). Çarpım ara adımda
+    // taşmasın diye ölçekli bakiye INDEX_PRECISION'a bölümü ve kalanı üzerinden çarpılır.
+    fn supply_amount(scaled_balance: i128, supply_index: i128) -> i128 {
+        scaled_balance / INDEX_PRECISION * supply_index + scaled_balance % INDEX_PRECISION * supply_index / INDEX_PRECISION
+    }
 
-        // Teminat miktarını güncelle
-        user_borrow.collateral_deposited += amount;
-        e.storage().instance().set(&user_borrow_key, &user_borrow);
+    // Ölçekli miktarın token karşılığı sığıyor mu (borcun yukarı yuvarlaması dahil).
+    // Faiz işlenirken havuz toplamları için doğrulandığından, toplamdan küçük kullanıcı
+    // bakiyelerinin karşılıkları da sığar.
+    fn checked_scaled_amount(scaled_balance: i128, index: i128) -> Option<i128> {
+        (scaled_balance / INDEX_PRECISION)
+            .checked_mul(index)?
+            .checked_add((scaled_balance % INDEX_PRECISION).checked_mul(index)? / INDEX_PRECISION + 1)
+    }
 
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    // Miktarın ölçekli karşılığı (aşağı yuvarlanır)
+    fn to_scaled_floor(amount: i128, index: i128) -> i128 {
+        amount * INDEX_PRECISION / index
+    }
 
-        emit_event(&e, "add_collateral", &user, amount);
+    // Ölçekli borcun token karşılığı (yukarı yuvarlanır, borç eksik hesaplanmaz)
+    fn debt_amount(scaled_debt: i128, borrow_index: i128) -> i128 {
+        scaled_debt / INDEX_PRECISION * borrow_index
+            + (scaled_debt % INDEX_PRECISION * borrow_index + INDEX_PRECISION - 1) / INDEX_PRECISION
     }
 
-    /// Kısmi teminat çekme
-    pub fn remove_collateral(e: Env, user: Address, amount: i128) {
-        user.require_auth();
-        check_nonnegative_amount(amount);
+    // Miktarın ölçekli karşılığı (yukarı yuvarlanır)
+    fn to_scaled_ceil(amount: i128, index: i128) -> i128 {
+        (amount * INDEX_PRECISION + index - 1) / index
+    }
 
-        // Kullanıcının hesabı dondurulmuş mu kontrol et
-        if is_account_frozen(&e, &user) {
-            panic!("Hesap dondurulmuş ve collateral çekme işlemi yapılamaz");
+    // Ödenen miktarı ölçekli borçtan düşer. Kısmi ödemede aşağı yuvarlanır ki
+    // borç olduğundan fazla azalmasın, tam ödemede borç sıfırlanır.
+    fn reduce_debt(lending_pool: &mut LendingPool, user_borrow: &mut UserBorrow, repay_amount: i128, total_debt: i128) {
+        let scaled_amount = if repay_amount >= total_debt {
+            user_borrow.scaled_debt
+        } else {
+            Self::to_scaled_floor(repay_amount, lending_pool.borrow_index).min(user_borrow.scaled_debt)
+        };
+        user_borrow.scaled_debt -= scaled_amount;
+        lending_pool.scaled_total_borrowed -= scaled_amount;
+    }
+
+    // Eski tek havuz henüz taşınmadıysa piyasa listesinde yer almaz
+    fn lending_markets(e: &Env) -> Vec<Address> {
+        let mut markets = read_markets(e);
+        let native = e.current_contract_address();
+        if !markets.contains(&native) && has_market(e, &native) {
+            markets.push_back(native);
         }
+        markets
+    }
 
-        let pool_key = DataKey::LendingPool;
-        let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
+    fn has_debt(e: &Env, user: &Address) -> bool {
+        Self::lending_markets(e)
+            .iter()
+            .any(|asset| read_user_borrow(e, &asset, user).is_some_and(|b| b.scaled_debt > 0))
+    }
 
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        let mut user_borrow: UserBorrow = e.storage().instance().get(&user_borrow_key)
-            .expect("No existing borrow position");
+    // Hesabın tüm piyasalardaki durumu (oracle'ın kote para biriminde):
+    // (teminat değeri, teminat faktörü uygulanmış borçlanma gücü, borç değeri).
+    // Teminat, borç pozisyonlarına yatırılan kontrat tokeni ile teminat olarak
+    // işaretlenmiş supply pozisyonlarından oluşur ve bulunduğu piyasanın teminat
+    // faktörüyle sayılır.
+    fn account_liquidity(e: &Env, user: &Address) -> (i128, i128, i128) {
+        let native = e.current_contract_address();
+        let mut collateral_value = 0;
+        let mut borrowing_power = 0;
+        let mut debt_value = 0;
 
-        // Faiz hesapla
-        let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-        let total_debt = user_borrow.amount + user_borrow.accrued_interest + interest_owed;
+        for asset in Self::lending_markets(e).iter() {
+            let user_borrow = read_user_borrow(e, &asset, user);
+            let user_supply = if read_use_as_collateral(e, &asset, user) {
+                read_user_supply(e, &asset, user)
+            } else {
+                None
+            };
+            if user_borrow.is_none() && user_supply.is_none() {
+                continue;
+            }
 
-        // Teminat çekildikten sonra pozisyonun sağlıklı kalacağını kontrol et
-        let remaining_collateral = user_borrow.collateral_deposited - amount;
-        let required_collateral = (total_debt * 10000) / lending_pool.collateral_factor as i128;
+            let lending_pool = Self::current_lending_pool(e, &asset);
+            let mut value = 0;
+            if let Some(user_borrow) = user_borrow {
+                if user_borrow.collateral_deposited > 0 {
+                    value += asset_value(e, &native, user_borrow.collateral_deposited);
+                }
+                if user_borrow.scaled_debt > 0 {
+                    let total_debt = Self::debt_amount(user_borrow.scaled_debt, lending_pool.borrow_index);
+                    // Değeri sıfıra yuvarlanan borç da karşılıksız sayılmaz
+                    debt_value += asset_value(e, &asset, total_debt).max(1);
+                }
+            }
+            if let Some(user_supply) = user_supply {
+                let supplied = Self::supply_amount(user_supply.scaled_balance, lending_pool.supply_index);
+                value += asset_value(e, &asset, supplied);
+            }
 
-        if remaining_collateral < required_collateral {
-            panic!("Removing collateral would make position unhealthy");
+            collateral_value += value;
+            borrowing_power += value * lending_pool.collateral_factor as i128 / 10000;
         }
 
-        // Teminat transferi
-        spend_balance(&e, e.current_contract_address(), amount);
-        receive_balance(&e, user.clone(), amount);
+        (collateral_value, borrowing_power, debt_value)
+    }
 
-        // Teminat miktarını güncelle
-        user_borrow.collateral_deposited -= amount;
-        e.storage().instance().set(&user_borrow_key, &user_borrow);
+    // Teminatın teminat faktörü uygulanmış değeri hesabın borçlarını karşılıyor mu
+    fn is_account_solvent(e: &Env, user: &Address) -> bool {
+        if !Self::has_debt(e, user) {
+            return true;
+        }
+        let (_, borrowing_power, debt_value) = Self::account_liquidity(e, user);
+        borrowing_power >= debt_value
+    }
 
-        e.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    // Teminat değerinin borç değerine oranı (yüzde, likidasyon eşiği uygulanmış).
+    // 100'ün altındaki hesaplar likide edilebilir.
+    fn account_health_factor(e: &Env, user: &Address) -> i128 {
+        if !Self::has_debt(e, user) {
+            return i128::MAX; // Sonsuz sağlık faktörü
+        }
 
-        emit_event(&e, "remove_collateral", &user, amount);
+        let liquidation_threshold: u32 = e.storage().instance().get(&DataKey::LiquidationThreshold)
+            .unwrap_or_else(|| panic_with_error!(e, ContractError::LendingPoolNotInitialized));
+        let (collateral_value, _, debt_value) = Self::account_liquidity(e, user);
+        (collateral_value * liquidation_threshold as i128) / (debt_value * 100)
     }
 
-    // ===============================
-    // YARDIMCI FONKSİYONLAR
-    // ===============================
-
-    fn accrue_lending_interest(e: &Env, lending_pool: &mut LendingPool) {
-        let current_ledger = e.ledger().sequence();
-        let ledgers_passed = current_ledger - lending_pool.last_update_ledger;
-        
-        if ledgers_passed > 0 {
-            // Basit faiz hesaplama (yıllık oranı ledger başına dönüştür)
-            // Varsayım: 1 yıl = 365 * 24 * 60 * 12 ledger (5 saniyelik ledger'lar)
-            let ledgers_per_year = 365 * 24 * 60 * 12;
-            
-            let borrow_interest = (lending_pool.total_borrowed * lending_pool.borrow_rate as i128 * ledgers_passed as i128) / (10000 * ledgers_per_year);
-            lending_pool.total_borrowed += borrow_interest;
-            
-            let supply_interest = (lending_pool.total_supplied * lending_pool.supply_rate as i128 * ledgers_passed as i128) / (10000 * ledgers_per_year);
-            lending_pool.total_supplied += supply_interest;
-            
-            lending_pool.last_update_ledger = current_ledger;
+    // Borcu kapanan pozisyon teminatı iade edilmeden silinmez. Teminat hesabın diğer
+    // borçlarını karşılamak için gerekiyorsa ya da hesaba giriş dondurulmuşsa pozisyon
+    // borçsuz olarak teminatla kalır; teminat sonradan remove_collateral ile çekilebilir
+    // ya da likidasyonda alınabilir.
+    fn close_borrow_position(e: &Env, asset: &Address, user: &Address, user_borrow: &UserBorrow) {
+        let collateral = user_borrow.collateral_deposited;
+        if collateral > 0 && !can_receive(e, user) {
+            write_user_borrow(e, asset, user, user_borrow);
+            return;
         }
+        if collateral > 0 {
+            // Hesap bu teminat olmadan da borçlarını karşılamalı
+            let released = UserBorrow { scaled_debt: 0, collateral_deposited: 0 };
+            write_user_borrow(e, asset, user, &released);
+            if !Self::is_account_solvent(e, user) {
+                write_user_borrow(e, asset, user, user_borrow);
+                return;
+            }
+            spend_balance(e, e.current_contract_address(), collateral);
+            receive_balance(e, user.clone(), collateral);
+            write_total_collateral(e, read_total_collateral(e) - collateral);
+        }
+        remove_user_borrow(e, asset, user);
     }
 
-    fn calculate_supply_interest(e: &Env, user_supply: &UserSupply, lending_pool: &LendingPool) -> i128 {
-        let current_ledger = e.ledger().sequence();
-        let ledgers_passed = current_ledger - user_supply.last_update_ledger;
-        
-        if ledgers_passed == 0 {
-            return 0;
+    // Borçlunun `asset` piyasasında teminat olarak işaretli supply pozisyonundan `value`
+    // değerindeki bakiyeyi liquidator'ın supply pozisyonuna devreder, karşılanamayan
+    // değeri döner. Ölçekli bakiye el değiştirdiği için havuz toplamları değişmez.
+    fn seize_supply_collateral(e: &Env, borrower: &Address, liquidator: &Address, asset: &Address, value: i128) -> i128 {
+        if !read_use_as_collateral(e, asset, borrower) {
+            return value;
         }
-        
-        let ledgers_per_year = 365 * 24 * 60 * 12;
-        (user_supply.amount * lending_pool.supply_rate as i128 * ledgers_passed as i128) / (10000 * ledgers_per_year)
-    }
+        let Some(mut borrower_supply) = read_user_supply(e, asset, borrower) else {
+            return value;
+        };
 
-    fn calculate_borrow_interest(e: &Env, user_borrow: &UserBorrow, lending_pool: &LendingPool) -> i128 {
-        let current_ledger = e.ledger().sequence();
-        let ledgers_passed = current_ledger - user_borrow.last_update_ledger;
-        
-        if ledgers_passed == 0 {
-            return 0;
+        let lending_pool = Self::current_lending_pool(e, asset);
+        let supplied = Self::supply_amount(borrower_supply.scaled_balance, lending_pool.supply_index);
+        let supplied_value = asset_value(e, asset, supplied);
+
+        let mut remaining_value = value;
+        let scaled_amount = if supplied_value <= remaining_value {
+            remaining_value -= supplied_value;
+            borrower_supply.scaled_balance
+        } else {
+            let amount = asset_amount(e, asset, remaining_value);
+            remaining_value = 0;
+            Self::to_scaled_ceil(amount, lending_pool.supply_index).min(borrower_supply.scaled_balance)
+        };
+
+        borrower_supply.scaled_balance -= scaled_amount;
+        if borrower_supply.scaled_balance == 0 {
+            remove_user_supply(e, asset, borrower);
+        } else {
+            write_user_supply(e, asset, borrower, &borrower_supply);
         }
-        
-        let ledgers_per_year = 365 * 24 * 60 * 12;
-        (user_borrow.amount * lending_pool.borrow_rate as i128 * ledgers_passed as i128) / (10000 * ledgers_per_year)
+
+        let mut liquidator_supply = read_user_supply(e, asset, liquidator)
+            .unwrap_or(UserSupply { scaled_balance: 0 });
+        liquidator_supply.scaled_balance += scaled_amount;
+        write_user_supply(e, asset, liquidator, &liquidator_supply);
+
+        remaining_value.max(0)
     }
 
     fn update_utilization_rate(lending_pool: &mut LendingPool) {
@@ -1350,86 +2701,101 @@ impl token::Interface for Token {
     // VIEW FONKSİYONLARI
     // ===============================
 
+    /// Açık lending piyasalarının token adresleri
+    pub fn get_markets(e: Env) -> Vec<Address> {
+        read_markets(&e)
+    }
+
     /// Lending havuz bilgilerini görüntüle
-    pub fn get_lending_pool_info(e: Env) -> LendingPool {
-        let pool_key = DataKey::LendingPool;
-        e.storage().instance().get(&pool_key).expect("Lending pool not initialized")
+    pub fn get_lending_pool_info(e: Env, asset: Address) -> LendingPool {
+        Self::read_lending_market(&e, &asset)
+    }
+
+    /// Fiyat oracle ayarlarını görüntüle
+    pub fn get_oracle_config(e: Env) -> Option<OracleConfig> {
+        read_oracle_config(&e)
+    }
+
+    /// Faiz modelini görüntüle (tanımlı değilse oranlar sabittir)
+    pub fn get_interest_rate_model(e: Env, asset: Address) -> Option<InterestRateModel> {
+        read_interest_rate_model(&e, &asset)
     }
 
     /// Kullanıcının supply bilgilerini görüntüle
-    pub fn get_user_supply_info(e: Env, user: Address) -> Option<UserSupply> {
-        let user_supply_key = DataKey::UserSupply(user);
-        e.storage().instance().get(&user_supply_key)
+    pub fn get_user_supply_info(e: Env, user: Address, asset: Address) -> Option<UserSupply> {
+        read_user_supply(&e, &asset, &user)
     }
 
     /// Kullanıcının borrow bilgilerini görüntüle
-    pub fn get_user_borrow_info(e: Env, user: Address) -> Option<UserBorrow> {
-        let user_borrow_key = DataKey::UserBorrow(user);
-        e.storage().instance().get(&user_borrow_key)
+    pub fn get_user_borrow_info(e: Env, user: Address, asset: Address) -> Option<UserBorrow> {
+        read_user_borrow(&e, &asset, &user)
+    }
+
+    /// Supply pozisyonu teminat olarak kullanılıyor mu
+    pub fn get_use_as_collateral(e: Env, user: Address, asset: Address) -> bool {
+        read_use_as_collateral(&e, &asset, &user)
     }
 
-    /// Kullanıcının sağlık faktörünü hesapla
+    /// Kullanıcının tüm piyasalardaki pozisyonları üzerinden sağlık faktörü
+    /// (borcu yoksa sonsuz)
     pub fn get_user_health_factor(e: Env, user: Address) -> i128 {
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        if let Some(user_borrow) = e.storage().instance().get::<_, UserBorrow>(&user_borrow_key) {
-            let pool_key = DataKey::LendingPool;
-            let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-                .expect("Lending pool not initialized");
-            
-            let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-            let total_debt = user_borrow.amount + user_borrow.accrued_interest + interest_owed;
-            
-            if total_debt == 0 {
-                return i128::MAX; // Sonsuz sağlık faktörü
+        Self::account_health_factor(&e, &user)
+    }
+
+    /// Kullanıcının faiz dahil güncel supply bakiyesi
+    pub fn get_user_supply_balance(e: Env, user: Address, asset: Address) -> i128 {
+        match read_user_supply(&e, &asset, &user) {
+            Some(user_supply) => {
+                let lending_pool = Self::current_lending_pool(&e, &asset);
+                Self::supply_amount(user_supply.scaled_balance, lending_pool.supply_index)
             }
-            
-            let liquidation_threshold: u32 = e.storage().instance().get(&DataKey::LiquidationThreshold).unwrap();
-            (user_borrow.collateral_deposited * liquidation_threshold as i128) / (total_debt * 100)
-        } else {
-            i128::MAX // Borcu yoksa sağlık faktörü sonsuz
+            None => 0,
         }
     }
 
-    /// Kullanıcının birikmiş supply faizini hesapla
-    pub fn get_pending_supply_interest(e: Env, user: Address) -> i128 {
-        let user_supply_key = DataKey::UserSupply(user);
-        if let Some(user_supply) = e.storage().instance().get::<_, UserSupply>(&user_supply_key) {
-            let pool_key = DataKey::LendingPool;
-            let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-                .expect("Lending pool not initialized");
-            
-            Self::calculate_supply_interest(&e, &user_supply, &lending_pool)
+    /// Kullanıcının faiz dahil güncel borcu
+    pub fn get_user_debt(e: Env, user: Address, asset: Address) -> i128 {
+        match read_user_borrow(&e, &asset, &user) {
+            Some(user_borrow) => {
+                let lending_pool = Self::current_lending_pool(&e, &asset);
+                Self::debt_amount(user_borrow.scaled_debt, lending_pool.borrow_index)
+            }
+            None => 0,
+        }
+    }
+
+    /// Kullanıcının havuz son güncellendiğinden beri biriken supply faizi
+    pub fn get_pending_supply_interest(e: Env, user: Address, asset: Address) -> i128 {
+        if let Some(user_supply) = read_user_supply(&e, &asset, &user) {
+            let stored_pool = Self::read_lending_market(&e, &asset);
+            let lending_pool = Self::current_lending_pool(&e, &asset);
+
+            Self::supply_amount(user_supply.scaled_balance, lending_pool.supply_index)
+                - Self::supply_amount(user_supply.scaled_balance, stored_pool.supply_index)
         } else {
             0
         }
     }
 
-    /// Kullanıcının birikmiş borrow faizini hesapla
-    pub fn get_pending_borrow_interest(e: Env, user: Address) -> i128 {
-        let user_borrow_key = DataKey::UserBorrow(user);
-        if let Some(user_borrow) = e.storage().instance().get::<_, UserBorrow>(&user_borrow_key) {
-            let pool_key = DataKey::LendingPool;
-            let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-                .expect("Lending pool not initialized");
-            
-            Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool)
+    /// Kullanıcının havuz son güncellendiğinden beri biriken borç faizi
+    pub fn get_pending_borrow_interest(e: Env, user: Address, asset: Address) -> i128 {
+        if let Some(user_borrow) = read_user_borrow(&e, &asset, &user) {
+            let stored_pool = Self::read_lending_market(&e, &asset);
+            let lending_pool = Self::current_lending_pool(&e, &asset);
+
+            Self::debt_amount(user_borrow.scaled_debt, lending_pool.borrow_index)
+                - Self::debt_amount(user_borrow.scaled_debt, stored_pool.borrow_index)
         } else {
             0
         }
     }
 
     /// Kullanıcı pozisyon özeti (supply, borrow, collateral, health factor)
-    pub fn get_user_position_summary(e: Env, user: Address) -> (i128, i128, i128, i128) {
-        let user_supply_key = DataKey::UserSupply(user.clone());
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-
-        let supply_info = e.storage().instance().get::<_, UserSupply>(&user_supply_key);
-        let borrow_info = e.storage().instance().get::<_, UserBorrow>(&user_borrow_key);
-
-        let total_supplied = supply_info.map_or(0, |s| s.amount + s.accrued_interest);
-        let total_borrowed = borrow_info.as_ref().map_or(0, |b| b.amount + b.accrued_interest);
-        let total_collateral = borrow_info.map_or(0, |b| b.collateral_deposited);
-        let health_factor = Self::get_user_health_factor(e, user);
+    pub fn get_user_position_summary(e: Env, user: Address, asset: Address) -> (i128, i128, i128, i128) {
+        let total_supplied = Self::get_user_supply_balance(e.clone(), user.clone(), asset.clone());
+        let total_borrowed = Self::get_user_debt(e.clone(), user.clone(), asset.clone());
+        let total_collateral = read_user_borrow(&e, &asset, &user).map_or(0, |b| b.collateral_deposited);
+        let health_factor = Self::account_health_factor(&e, &user);
 
         (total_supplied, total_borrowed, total_collateral, health_factor)
     }
@@ -1438,35 +2804,32 @@ impl token::Interface for Token {
     // ADMIN FONKSİYONLARI
     // ===============================
 
-    /// Lending faiz oranlarını güncelle (sadece admin)
-    pub fn update_lending_rates(e: Env, new_supply_rate: u32, new_borrow_rate: u32) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    /// Lending faiz oranlarını güncelle (timelock üzerinden). Faiz modeli tanımlıysa
+    /// oranlar bir sonraki faiz işlemesinde modelden yeniden türetilir.
+    fn update_lending_rates(e: Env, caller: Address, asset: Address, new_supply_rate: u32, new_borrow_rate: u32) {
+        check_lending_rates(&e, new_supply_rate, new_borrow_rate);
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+
+        // Eski oranlarla biriken faizi yeni oranlara geçmeden önce işle
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
         lending_pool.supply_rate = new_supply_rate;
         lending_pool.borrow_rate = new_borrow_rate;
-        
-        e.storage().instance().set(&pool_key, &lending_pool);
+
+        write_market(&e, &asset, &lending_pool);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "update_lending_rates", &admin, new_supply_rate as i128);
+        emit_market_event(&e, "update_lending_rates", &asset, &caller, new_supply_rate as i128);
     }
 
-    /// Liquidation parametrelerini güncelle (sadece admin)
-    pub fn update_liquidation_params(e: Env, threshold: u32, penalty: u32) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
-
+    /// Liquidation parametrelerini güncelle (timelock üzerinden)
+    fn update_liquidation_params(e: Env, caller: Address, threshold: u32, penalty: u32) {
         let threshold_key = DataKey::LiquidationThreshold;
         let penalty_key = DataKey::LiquidationPenalty;
-        
+
         e.storage().instance().set(&threshold_key, &threshold);
         e.storage().instance().set(&penalty_key, &penalty);
 
@@ -1474,119 +2837,97 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "update_liquidation_params", &admin, threshold as i128);
+        emit_event(&e, "update_liquidation_params", &caller, threshold as i128);
     }
 
-    /// Collateral faktörünü güncelle (sadece admin)
-    pub fn update_collateral_factor(e: Env, new_factor: u32) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    /// Collateral faktörünü güncelle (timelock üzerinden)
+    fn update_collateral_factor(e: Env, caller: Address, asset: Address, new_factor: u32) {
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
 
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
         lending_pool.collateral_factor = new_factor;
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_market(&e, &asset, &lending_pool);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "update_collateral_factor", &admin, new_factor as i128);
+        emit_market_event(&e, "update_collateral_factor", &asset, &caller, new_factor as i128);
     }
 
-    /// Dinamik faiz oranı hesaplama ve güncelleme (sadece admin)
-    pub fn update_dynamic_rates(e: Env) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    /// Kullanıma bağlı faiz modelini güncelle (timelock üzerinden)
+    fn update_interest_rate_model(e: Env, caller: Address, asset: Address, model: InterestRateModel) {
+        if !interest::is_valid_model(&model) {
+            panic_with_error!(&e, MarketError::InvalidInterestRateModel);
+        }
 
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
 
-        // Kullanım oranına göre dinamik faiz hesapla
-        let utilization = lending_pool.utilization_rate;
+        // Eski oranlarla biriken faizi yeni modele geçmeden önce işle
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
-        // Jump Rate Model:
-        // Utilization < 80%: Linear artış
-        // Utilization >= 80%: Exponential artış
-        let optimal_utilization = 8000; // %80
-        let base_rate = 200; // %2
-        let multiplier = 500; // %5
-        let jump_multiplier = 10000; // %100
+        write_interest_rate_model(&e, &asset, &model);
+        Self::apply_interest_rate_model(&model, &mut lending_pool);
+        write_market(&e, &asset, &lending_pool);
 
-        let new_borrow_rate = if utilization <= optimal_utilization {
-            base_rate + (utilization * multiplier / 10000)
-        } else {
-            let excess_utilization = utilization - optimal_utilization;
-            base_rate + (optimal_utilization * multiplier / 10000) + 
-            (excess_utilization * jump_multiplier / 10000)
-        };
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        // Supply rate = borrow rate * utilization * (1 - reserve factor)
-        let new_supply_rate = (new_borrow_rate * utilization / 10000) * 
-                             (10000 - lending_pool.reserve_factor) / 10000;
+        emit_market_event(&e, "update_interest_rate_model", &asset, &caller, lending_pool.borrow_rate as i128);
+    }
 
-        lending_pool.borrow_rate = new_borrow_rate;
-        lending_pool.supply_rate = new_supply_rate;
+    /// Fiyat oracle'ını ve kontrol parametrelerini güncelle (timelock üzerinden)
+    fn update_oracle(e: Env, caller: Address, config: OracleConfig) {
+        if config.max_age == 0 || config.max_deviation_bps == 0 {
+            panic_with_error!(&e, MarketError::InvalidOracleConfig);
+        }
 
-        e.storage().instance().set(&pool_key, &lending_pool);
+        write_oracle_config(&e, &config);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "dynamic_rate_update", &admin, new_borrow_rate as i128);
+        e.events().publish(("update_oracle", caller), config.oracle);
     }
 
-    /// Protokol rezervlerini çek (sadece admin)
-    pub fn withdraw_reserves(e: Env, amount: i128) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
-        check_nonnegative_amount(amount);
-
-        let pool_key = DataKey::LendingPool;
-        let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
+    /// Protokol rezervlerini çek (sadece hazine rolü)
+    pub fn withdraw_reserves(e: Env, caller: Address, asset: Address, amount: i128) {
+        caller.require_auth();
+        check_role(&e, Role::Treasurer, &caller);
+        check_nonnegative_amount(&e, amount);
 
-        // Rezerv miktarını hesapla
-        let total_interest_earned = if lending_pool.total_supplied > lending_pool.total_borrowed {
-            lending_pool.total_supplied - lending_pool.total_borrowed
-        } else {
-            0
-        };
-
-        let available_reserves = (total_interest_earned * lending_pool.reserve_factor as i128) / 10000;
+        // Sadece borç faizinden ayrılmış rezerv çekilebilir, supply likiditesi kullanıcılarındır
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
 
-        if amount > available_reserves {
-            panic!("Insufficient reserves");
+        if amount > lending_pool.total_reserves {
+            panic_with_error!(&e, ContractError::InsufficientReserves);
         }
+        lending_pool.total_reserves -= amount;
+        write_market(&e, &asset, &lending_pool);
 
-        // Rezervleri admin'e transfer et
-        spend_balance(&e, e.current_contract_address(), amount);
-        receive_balance(&e, admin.clone(), amount);
+        // Rezervleri çağırana transfer et
+        transfer_pool_token(&e, &asset, &e.current_contract_address(), &caller, amount);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "withdraw_reserves", &admin, amount);
+        emit_market_event(&e, "withdraw_reserves", &asset, &caller, amount);
     }
 
-    /// Risk analizi metrikleri (sadece admin)
-    pub fn get_protocol_risk_metrics(e: Env) -> (i128, i128, u32, u32) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    /// Risk analizi metrikleri (sadece risk yöneticisi)
+    pub fn get_protocol_risk_metrics(e: Env, caller: Address, asset: Address) -> (i128, i128, u32, u32) {
+        caller.require_auth();
+        check_role(&e, Role::RiskManager, &caller);
 
-        let pool_key = DataKey::LendingPool;
-        let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
+        let lending_pool = Self::current_lending_pool(&e, &asset);
 
         let total_value_locked = lending_pool.total_supplied;
         let total_debt = lending_pool.total_borrowed;
         let utilization_rate = lending_pool.utilization_rate;
-        
+
         // Risk skoru hesapla (utilization rate bazlı)
         let risk_score = if utilization_rate > 9000 {
             100 // Yüksek risk
@@ -1601,44 +2942,133 @@ impl token::Interface for Token {
         (total_value_locked, total_debt, utilization_rate, risk_score)
     }
 
-    /// Acil durum lending pool çekimi (sadece admin)
-    pub fn emergency_withdraw_lending_pool(e: Env) -> i128 {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    /// Acil durum lending pool çekimi (sadece hazine rolü)
+    pub fn emergency_withdraw_lending_pool(e: Env, caller: Address, asset: Address) -> i128 {
+        caller.require_auth();
+        check_role(&e, Role::Treasurer, &caller);
+        Self::withdraw_lending_funds(e, asset, caller)
+    }
 
-        // Kontrattaki toplam bakiyeyi al
-        let balance = read_balance(&e, e.current_contract_address());
+    // Piyasanın kontrattaki nakdi (supply ve rezervler eksi borçlar) çekilir. Kendi
+    // tokenindeki teminat, vesting emaneti ve staking fonları kontratta bırakılır.
+    // Kullanıcı kayıtları ve havuz toplamları değişmez; alacakları karşılıksız kalan
+    // supply'ların çekilmemesi için piyasa supply, çekim ve borca kapanır. Geri
+    // ödeme ve likidasyon açık kalır, sonradan gelen geri ödemeler de çekilebilir.
+    fn withdraw_lending_funds(e: Env, asset: Address, recipient: Address) -> i128 {
+        let mut lending_pool = Self::read_lending_market(&e, &asset);
+        Self::accrue_lending_interest(&e, &asset, &mut lending_pool);
+
+        let mut available = pool_token_balance(&e, &asset);
+        if asset == e.current_contract_address() {
+            available -= read_vesting_escrow(&e) + read_total_collateral(&e);
+        }
+        available -= Self::staking_funds(&e, &asset);
+        let balance = Self::market_cash(&e, &asset, &lending_pool).min(available);
 
-        // Tüm bakiyeyi admin'e transfer et
         if balance > 0 {
-            spend_balance(&e, e.current_contract_address(), balance);
-            receive_balance(&e, admin.clone(), balance);
-
-            // Lending pool'u sıfırla
-            let pool_key = DataKey::LendingPool;
-            if let Ok(mut lending_pool) = e.storage().instance().get::<_, LendingPool>(&pool_key) {
-                lending_pool.total_supplied = 0;
-                lending_pool.total_borrowed = 0;
-                lending_pool.utilization_rate = 0;
-                e.storage().instance().set(&pool_key, &lending_pool);
-            }
+            transfer_pool_token(&e, &asset, &e.current_contract_address(), &recipient, balance);
+
+            // Rezervler de çekilen nakdin parçasıdır
+            lending_pool.total_reserves = 0;
+            let swept = read_market_swept(&e, &asset).unwrap_or(0);
+            write_market_swept(&e, &asset, swept + balance);
+
+            emit_market_event(&e, "emergency_withdraw_lending", &asset, &recipient, balance);
+        }
+        write_market(&e, &asset, &lending_pool);
 
-            emit_event(&e, "emergency_withdraw_lending", &admin, balance);
+        balance.max(0)
+    }
+
+    // Piyasanın kontratta durması gereken nakdi: supply ve rezervler eksi borçlar,
+    // daha önce acil çekimle alınan kısım hariç
+    fn market_cash(e: &Env, asset: &Address, lending_pool: &LendingPool) -> i128 {
+        let swept = read_market_swept(e, asset).unwrap_or(0);
+        (lending_pool.total_supplied - lending_pool.total_borrowed + lending_pool.total_reserves - swept).max(0)
+    }
+
+    // Lending'in kontratta `token` cinsinden tuttuğu fonlar: o tokenin piyasasının
+    // nakdi ve kendi tokenindeyse borç pozisyonlarının teminatı
+    fn lending_funds(e: &Env, token: &Address) -> i128 {
+        let mut funds = 0;
+        if let Some(mut lending_pool) = read_market(e, token) {
+            Self::accrue_lending_interest(e, token, &mut lending_pool);
+            funds += Self::market_cash(e, token, &lending_pool);
+        }
+        if *token == e.current_contract_address() {
+            funds += read_total_collateral(e);
+        }
+        funds
+    }
+
+    // Staking'in kontratta `token` cinsinden tuttuğu fonlar: stake edilen anapara ve
+    // dağıtılmamış ödüller. Kazanılmış ödüller kullanıcı bazında tutulduğu için
+    // sayılamaz; onları çekebilecek acil çekimler kendi fonlarıyla sınırlıdır.
+    fn staking_funds(e: &Env, token: &Address) -> i128 {
+        let Some(mut pool_info) = e.storage().instance().get::<_, PoolInfo>(&POOL_INFO_KEY) else {
+            return 0;
+        };
+        Self::update_reward(e, &mut pool_info, None);
+        let mut funds = 0;
+        if pool_info.token_id == *token {
+            funds += pool_info.total_staked;
+        }
+        if pool_info.reward_token_id == *token {
+            funds += Self::undistributed_rewards(e, &pool_info);
+        }
+        funds
+    }
+
+    // Devam eden ödül döneminin henüz dağıtılmamış kısmı
+    fn undistributed_rewards(e: &Env, pool_info: &PoolInfo) -> i128 {
+        let current_ledger = e.ledger().sequence();
+        if current_ledger < pool_info.period_finish {
+            (pool_info.period_finish - current_ledger) as i128 * pool_info.reward_rate
+        } else {
+            0
+        }
+    }
+
+    // Teminat takibinden önceki kayıtlar sayılamadığı için kendi token bakiyesinin
+    // vesting, staking ve kendi piyasasına ait olmayan kısmının tamamı teminat kabul
+    // edilir. Fazla sayılan kısım acil çekimlerle alınamaz ama teminat eksik kalmaz.
+    fn migrate_total_collateral(e: &Env) {
+        if e.storage().instance().has(&DataKey::TotalCollateral) {
+            return;
         }
+        let native = e.current_contract_address();
+        let mut collateral = read_balance(e, native.clone()) - read_vesting_escrow(e) - Self::staking_funds(e, &native);
+        if let Some(lending_pool) = read_market(e, &native) {
+            collateral -= Self::market_cash(e, &native, &lending_pool);
+        }
+        write_total_collateral(e, collateral);
+    }
 
-        balance
+    // Acil çekimle nakdi alınan piyasada yeni pozisyon açılamaz ve supply çekilemez
+    fn check_market_open(e: &Env, asset: &Address) {
+        if read_market_swept(e, asset).is_some() {
+            panic_with_error!(e, MarketError::MarketClosed);
+        }
     }
 
     // ===============================
     // GELİŞMİŞ FONKSİYONLAR
     // ===============================
 
-    /// Toplu liquidation (birden fazla pozisyonu aynı anda tasfiye et)
-    pub fn batch_liquidate(e: Env, liquidator: Address, targets: Vec<(Address, i128)>) {
+    /// Toplu liquidation (birden fazla pozisyonu aynı anda tasfiye et, teminat hepsinde
+    /// `collateral_asset` piyasasından alınır)
+    pub fn batch_liquidate(
+        e: Env,
+        liquidator: Address,
+        asset: Address,
+        collateral_asset: Address,
+        targets: Vec<(Address, i128)>,
+    ) {
         liquidator.require_auth();
+        check_not_paused(&e, PauseOperation::Liquidate);
 
         if targets.len() > 10 {
-            panic!("Too many targets, maximum 10 allowed");
+            panic_with_error!(&e, ContractError::TooManyTargets);
         }
 
         let mut total_repaid = 0i128;
@@ -1648,30 +3078,30 @@ impl token::Interface for Token {
             let amount = target.1;
 
             // Her liquidation için health factor kontrol et
-            let health_factor = Self::get_user_health_factor(e.clone(), borrower.clone());
-            
+            let health_factor = Self::account_health_factor(&e, &borrower);
+
             if health_factor >= 100 {
                 continue; // Sağlıklı pozisyon, atla
             }
 
             // Liquidation işlemini gerçekleştir
-            Self::liquidate(e.clone(), liquidator.clone(), borrower, amount);
+            Self::liquidate(e.clone(), liquidator.clone(), borrower, asset.clone(), collateral_asset.clone(), amount);
             total_repaid += amount;
         }
 
-        emit_event(&e, "batch_liquidate", &liquidator, total_repaid);
+        emit_market_event(&e, "batch_liquidate", &asset, &liquidator, total_repaid);
     }
 
     /// Sağlıksız pozisyonları tespit et
-    pub fn find_liquidatable_positions(e: Env, users: Vec<Address>) -> Vec<Address> {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    pub fn find_liquidatable_positions(e: Env, caller: Address, users: Vec<Address>) -> Vec<Address> {
+        caller.require_auth();
+        check_role(&e, Role::RiskManager, &caller);
 
         let mut liquidatable_users = Vec::new(&e);
 
         for user in users.iter() {
-            let health_factor = Self::get_user_health_factor(e.clone(), user.clone());
-            
+            let health_factor = Self::account_health_factor(&e, &user);
+
             // Sağlık faktörü 100'ün altındaysa (pozisyon sağlıksız)
             if health_factor < 100 {
                 liquidatable_users.push_back(user.clone());
@@ -1682,54 +3112,158 @@ impl token::Interface for Token {
     }
 
     /// Lending havuzu manuel faiz güncelleme
-    pub fn accrue_lending_interest_manual(e: Env) {
-        let pool_key = DataKey::LendingPool;
-        let mut lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-        
-        Self::accrue_lending_interest(&e, &mut lending_pool);
-        e.storage().instance().set(&pool_key, &lending_pool);
+    pub fn accrue_lending_interest_manual(e: Env, asset: Address) {
+        let lending_pool = Self::current_lending_pool(&e, &asset);
+        write_market(&e, &asset, &lending_pool);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        emit_event(&e, "manual_interest_accrual", &e.current_contract_address(), 0);
+        emit_market_event(&e, "manual_interest_accrual", &asset, &e.current_contract_address(), 0);
     }
 
     /// Kullanıcının maksimum borçlanabileceği miktarı hesapla
-    pub fn get_max_borrowable_amount(e: Env, user: Address, collateral_amount: i128) -> i128 {
-        let pool_key = DataKey::LendingPool;
-        let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
-
-        // Mevcut borrow pozisyonunu al
-        let user_borrow_key = DataKey::UserBorrow(user.clone());
-        let current_debt = if let Some(user_borrow) = e.storage().instance().get::<_, UserBorrow>(&user_borrow_key) {
-            let interest_owed = Self::calculate_borrow_interest(&e, &user_borrow, &lending_pool);
-            user_borrow.amount + user_borrow.accrued_interest + interest_owed
+    pub fn get_max_borrowable_amount(e: Env, user: Address, asset: Address, collateral_amount: i128) -> i128 {
+        let lending_pool = Self::current_lending_pool(&e, &asset);
+
+        // Hesabın mevcut borçlanma gücü ve borçları
+        let (_, borrowing_power, debt_value) = Self::account_liquidity(&e, &user);
+
+        // Maksimum borçlanabilir değer = borçlanma gücü + (eklenecek teminat değeri * collateral_factor / 10000) - borç değeri
+        let collateral_value = if collateral_amount > 0 {
+            asset_value(&e, &e.current_contract_address(), collateral_amount)
         } else {
             0
         };
+        let max_debt_value = borrowing_power + collateral_value * lending_pool.collateral_factor as i128 / 10000;
 
-        // Maksimum borçlanabilir miktar = (collateral * collateral_factor / 10000) - current_debt
-        let max_total_debt = (collateral_amount * lending_pool.collateral_factor as i128) / 10000;
-        
-        if max_total_debt > current_debt {
-            max_total_debt - current_debt
+        if max_debt_value > debt_value {
+            asset_amount(&e, &asset, max_debt_value - debt_value)
         } else {
             0
         }
     }
 
     /// Havuzdaki mevcut likiditeyi kontrol et
-    pub fn get_available_liquidity(e: Env) -> i128 {
-        let pool_key = DataKey::LendingPool;
-        let lending_pool: LendingPool = e.storage().instance().get(&pool_key)
-            .expect("Lending pool not initialized");
+    pub fn get_available_liquidity(e: Env, asset: Address) -> i128 {
+        let lending_pool = Self::current_lending_pool(&e, &asset);
 
         lending_pool.total_supplied - lending_pool.total_borrowed
     }
+}
+  
+
+#[contractimpl]
+impl token::Interface for Token {
+    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_allowance(&e, from, spender).amount
+    }
+
+    fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+
+        check_nonnegative_amount(&e, amount);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_allowance(&e, from.clone(), spender.clone(), amount, expiration_ledger);
+        TokenUtils::new(&e)
+            .events()
+            .approve(from, spender, amount, expiration_ledger);
+    }
+
+    fn balance(e: Env, id: Address) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_balance(&e, id)
+    }
+
+    fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        check_not_paused(&e, PauseOperation::Transfer);
+
+        check_nonnegative_amount(&e, amount);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Gönderici ve alıcının hesabı dondurulmuş mu kontrol et
+        check_can_send(&e, &from);
+        check_can_receive(&e, &to);
+
+        // Transferi gerçekleştir
+        spend_balance(&e, from.clone(), amount);
+        receive_balance(&e, to.clone(), amount);
+        TokenUtils::new(&e).events().transfer(from, to, amount);
+    }
+
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        check_not_paused(&e, PauseOperation::Transfer);
+
+        check_nonnegative_amount(&e, amount);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Gönderici ve alıcının hesabı dondurulmuş mu kontrol et
+        check_can_send(&e, &from);
+        check_can_receive(&e, &to);
+
+         // Transferi gerçekleştir
+        spend_allowance(&e, from.clone(), spender, amount);
+        spend_balance(&e, from.clone(), amount);
+        receive_balance(&e, to.clone(), amount);
+        TokenUtils::new(&e).events().transfer(from, to, amount)
+    }
+
+    fn burn(e: Env, from: Address, amount: i128) {
+        from.require_auth();
+        check_not_paused(&e, PauseOperation::MintBurn);
+
+        check_nonnegative_amount(&e, amount);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // Göndericinin hesabı dondurulmuş mu kontrol et
+        check_can_send(&e, &from);
+
+        // Yakma işlemini gerçekleştir
+        spend_balance(&e, from.clone(), amount);
+        reduce_total_supply(&e, amount);
+        TokenUtils::new(&e).events().burn(from, amount);
+    }
+
+    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        check_not_paused(&e, PauseOperation::MintBurn);
+
+        check_nonnegative_amount(&e, amount);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+         // Göndericinin hesabı dondurulmuş mu kontrol et
+         check_can_send(&e, &from);
+
+        // Yakma işlemini gerçekleştir
+        spend_allowance(&e, from.clone(), spender, amount);
+        spend_balance(&e, from.clone(), amount);
+        reduce_total_supply(&e, amount);
+        TokenUtils::new(&e).events().burn(from, amount)
+    }
     fn decimals(e: Env) -> u32 {
         read_decimal(&e)
     }
@@ -0,0 +1,40 @@
+use soroban_sdk::Env;
+
+use crate::storage_types::{Council, CouncilProposal, DataKey};
+
+pub fn read_council(e: &Env) -> Option<Council> {
+    let key = DataKey::Council;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_council(e: &Env, council: &Council) {
+    let key = DataKey::Council;
+    e.storage().instance().set(&key, council);
+}
+
+pub fn remove_council(e: &Env) {
+    let key = DataKey::Council;
+    e.storage().instance().remove(&key);
+}
+
+pub fn read_council_proposal(e: &Env, id: u64) -> Option<CouncilProposal> {
+    let key = DataKey::CouncilProposal(id);
+    e.storage().instance().get(&key)
+}
+
+pub fn write_council_proposal(e: &Env, proposal: &CouncilProposal) {
+    let key = DataKey::CouncilProposal(proposal.id);
+    e.storage().instance().set(&key, proposal);
+}
+
+pub fn remove_council_proposal(e: &Env, id: u64) {
+    let key = DataKey::CouncilProposal(id);
+    e.storage().instance().remove(&key);
+}
+
+pub fn next_council_proposal_id(e: &Env) -> u64 {
+    let key = DataKey::NextCouncilProposalId;
+    let id: u64 = e.storage().instance().get(&key).unwrap_or(0);
+    e.storage().instance().set(&key, &(id + 1));
+    id
+}
@@ -0,0 +1,114 @@
+use soroban_sdk::contracterror;
+
+// Kontratın döndürdüğü hata kodları. Numaralar istemciler tarafından kullanıldığı
+// için mevcut değerler değiştirilmemeli, yeni hatalar ilgili grubun sonuna eklenmeli.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    // Token hataları
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    DecimalTooLarge = 3,
+    NegativeAmount = 4,
+    InsufficientBalance = 5,
+    InsufficientAllowance = 6,
+    InvalidExpirationLedger = 7,
+    AccountFrozen = 8,
+    NoPendingAdmin = 9,
+    AdminProposalExpired = 10,
+
+    // Vesting hataları
+    InvalidVestingPeriod = 100,
+    InvalidCliff = 101,
+    VestingNotFound = 102,
+    NothingToClaim = 103,
+    InvalidUnlockPercent = 105,
+    VestingNotRevocable = 106,
+    VestingBatchTooLarge = 107,
+
+    // Staking hataları
+    StakingAlreadyInitialized = 200,
+    StakingNotInitialized = 201,
+    InvalidStakeAmount = 202,
+    StakeNotFound = 203,
+    NoRewardsToClaim = 204,
+    UnstakeExceedsStake = 205,
+    MinStakeDurationNotMet = 206,
+    InvalidRewardAmount = 207,
+    InvalidRewardDuration = 208,
+    RewardPeriodActive = 209,
+
+    // Lending hataları
+    LendingPoolAlreadyInitialized = 300,
+    LendingPoolNotInitialized = 301,
+    SupplyNotFound = 302,
+    InsufficientSupply = 303,
+    InsufficientLiquidity = 304,
+    BorrowNotFound = 305,
+    InsufficientCollateral = 306,
+    PositionHealthy = 307,
+    PositionUnhealthy = 308,
+    NotEnoughCollateralToSeize = 309,
+    InsufficientReserves = 310,
+    TooManyTargets = 311,
+
+    // Durdurma hataları
+    OperationPaused = 400,
+
+    // Yetki hataları
+    MissingRole = 500,
+    NotAdmin = 501,
+
+    // Timelock hataları
+    OperationNotFound = 600,
+    OperationNotReady = 601,
+
+    // Admin konseyi hataları
+    CouncilAlreadyInitialized = 700,
+    CouncilNotInitialized = 701,
+    NotCouncilSigner = 702,
+    InvalidCouncil = 703,
+    ProposalNotFound = 704,
+    AlreadyApproved = 705,
+}
+
+// Arz ve mint kontrolleri ile ilgili hata kodları. Bir contracterror enum'u en fazla
+// 50 hata taşıyabildiği için yeni gruplar ContractError yerine buraya eklenir.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SupplyError {
+    SupplyCapExceeded = 900,
+    InvalidMaxSupply = 901,
+    MintLimitExceeded = 902,
+    InvalidMintLimit = 903,
+    ClawbackDisabled = 904,
+    TotalSupplyRequired = 905,
+    InvalidTotalSupply = 906,
+    SupplyUnderflow = 907,
+}
+
+// Lending piyasaları ile ilgili yeni hata kodları
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum MarketError {
+    InvalidInterestRateModel = 1000,
+    InvalidOracleConfig = 1001,
+    OracleNotConfigured = 1002,
+    PriceUnavailable = 1003,
+    StalePrice = 1004,
+    PriceDeviationTooHigh = 1005,
+    MarketClosed = 1006,
+    InvalidLendingRates = 1007,
+    InterestOverflow = 1008,
+}
+
+// Storage şeması taşımaları ile ilgili hata kodları
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SchemaError {
+    AlreadyMigrated = 1100,
+}
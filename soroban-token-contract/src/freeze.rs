@@ -0,0 +1,40 @@
+use soroban_sdk::{Address, Env};
+
+use crate::storage_types::{
+    DataKey, FreezeMode, FreezeStatus, FREEZE_BUMP_AMOUNT, FREEZE_LIFETIME_THRESHOLD,
+};
+
+pub fn read_freeze_status(e: &Env, account: &Address) -> Option<FreezeStatus> {
+    let key = DataKey::FreezeStatus(account.clone());
+    if let Some(status) = e.storage().persistent().get::<DataKey, FreezeStatus>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, FREEZE_LIFETIME_THRESHOLD, FREEZE_BUMP_AMOUNT);
+        return Some(status);
+    }
+    // Eski sürümde dondurma instance storage'da sadece bir bool olarak tutuluyordu
+    let legacy_key = DataKey::Frozen(account.clone());
+    match e.storage().instance().get::<_, bool>(&legacy_key) {
+        Some(true) => Some(FreezeStatus {
+            mode: FreezeMode::All,
+            reason: 0,
+            since_ledger: 0,
+        }),
+        _ => None,
+    }
+}
+
+pub fn write_freeze_status(e: &Env, account: &Address, status: &FreezeStatus) {
+    let key = DataKey::FreezeStatus(account.clone());
+    e.storage().persistent().set(&key, status);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, FREEZE_LIFETIME_THRESHOLD, FREEZE_BUMP_AMOUNT);
+    e.storage().instance().remove(&DataKey::Frozen(account.clone()));
+}
+
+pub fn remove_freeze_status(e: &Env, account: &Address) {
+    let key = DataKey::FreezeStatus(account.clone());
+    e.storage().persistent().remove(&key);
+    e.storage().instance().remove(&DataKey::Frozen(account.clone()));
+}
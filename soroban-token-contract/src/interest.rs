@@ -0,0 +1,104 @@
+use crate::storage_types::InterestRateModel;
+
+// Faiz oranları yıllık baz puan olarak tutulur, birikim saniye bazında bileşiklenir
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+pub const BASIS_POINTS: i128 = 10_000;
+// Bileşik faktör hesabının ara hassasiyeti (indeks hassasiyetinden yüksek tutulur)
+pub const WAD: i128 = 1_000_000_000_000_000_000;
+// Piyasa oranlarının ve faiz modeli bileşenlerinin toplamının üst sınırı (yıllık %300).
+// Bu oranda indeksler yıllarca işlem görmeyen bir piyasada da i128 sınırına uzak kalır.
+pub const MAX_RATE_BPS: u32 = 30_000;
+
+// Tek bir binom açılımıyla bileşiklenen en uzun süre. Daha uzun aralıklar bu
+// uzunlukta parçalara bölünür ve parçaların faktörleri çarpılır; böylece yüksek
+// oranlarda uzun boşluklarda bile binom terimleri küçük kalır.
+pub const MAX_COMPOUND_PERIOD: u64 = SECONDS_PER_YEAR;
+
+// `elapsed` saniye boyunca yıllık `rate_bps` oranıyla saniyelik bileşiklenmenin
+// büyüme faktörü, yani (1 + r)^n (WAD ile ölçekli), r = rate_bps / (10000 * yıl).
+// Faktör i128 ile gösterilemeyecek kadar büyürse None döner.
+pub fn compound_factor(rate_bps: u32, elapsed: u64) -> Option<i128> {
+    let mut factor = WAD;
+    let mut remaining = elapsed;
+    while remaining > 0 {
+        let period = remaining.min(MAX_COMPOUND_PERIOD);
+        factor = mul_div(factor, period_factor(rate_bps, period)?, WAD)?;
+        remaining -= period;
+    }
+    Some(factor)
+}
+
+// Tek bir parçanın büyüme faktörü. Binom açılımı terimleri sırayla hesaplanır:
+//   t_0 = 1, t_k = t_(k-1) * r * (n - k + 1) / k
+// ve terimler sabit nokta hassasiyetinde sıfıra düştüğünde durulur. Her adımda
+// r'nin pay ve paydası ayrı çarpıldığı için saniyelik oranın yuvarlama hatası oluşmaz.
+// Taşma durumunda None döner.
+fn period_factor(rate_bps: u32, elapsed: u64) -> Option<i128> {
+    let rate = rate_bps as i128;
+    let n = elapsed as i128;
+    let denominator = BASIS_POINTS * SECONDS_PER_YEAR as i128;
+
+    let mut factor = WAD;
+    let mut term = WAD;
+    let mut k: i128 = 1;
+    while k <= n {
+        term = mul_div(term, rate * (n - k + 1), denominator * k)?;
+        if term == 0 {
+            break;
+        }
+        factor = factor.checked_add(term)?;
+        k += 1;
+    }
+    Some(factor)
+}
+
+// Negatif olmayan değerler için a * b / d (aşağı yuvarlanır). Büyük çarpan d'ye
+// bölümü ve kalanı üzerinden ayrı çarpılır ki a * b ara adımda taşmasın; sonuç
+// (ya da kalanla küçük çarpanın çarpımı) i128'e sığmazsa None döner.
+fn mul_div(a: i128, b: i128, d: i128) -> Option<i128> {
+    let (a, b) = if a >= b { (a, b) } else { (b, a) };
+    let whole = (a / d).checked_mul(b)?;
+    let fraction = (a % d).checked_mul(b)? / d;
+    whole.checked_add(fraction)
+}
+
+// İndeksi geçen süre boyunca bileşik faizle büyütür (aşağı yuvarlanır). Sonuç i128
+// ile gösterilemiyorsa None döner; çağıran faizi işlemeyi reddetmelidir.
+pub fn accrue_index(index: i128, rate_bps: u32, elapsed: u64) -> Option<i128> {
+    if rate_bps == 0 || elapsed == 0 {
+        return Some(index);
+    }
+    compound_factor(rate_bps, elapsed).and_then(|factor| mul_div(index, factor, WAD))
+}
+
+// Faiz modelinin her kullanım oranında MAX_RATE_BPS'i aşmayan bir oran verdiğini doğrular
+pub fn is_valid_model(model: &InterestRateModel) -> bool {
+    model.kink > 0
+        && model.kink < 10_000
+        && model.base_rate as u64 + model.slope1 as u64 + model.slope2 as u64 <= MAX_RATE_BPS as u64
+}
+
+// Eski sürümdeki ledger sayısına dayalı güncelleme zamanını zaman damgasına çevirir
+// (5 saniyelik ledger varsayımıyla)
+pub fn ledger_to_timestamp(current_timestamp: u64, current_ledger: u32, ledger: u32) -> u64 {
+    let ledgers_ago = current_ledger.saturating_sub(ledger) as u64;
+    current_timestamp.saturating_sub(ledgers_ago * 5)
+}
+
+// Kullanım oranına göre yıllık borç oranı (baz puan)
+pub fn borrow_rate(model: &InterestRateModel, utilization: u32) -> u32 {
+    let utilization = utilization as u64;
+    let kink = model.kink as u64;
+    let rate = if utilization <= kink {
+        model.base_rate as u64 + model.slope1 as u64 * utilization / kink
+    } else {
+        let excess_utilization = utilization - kink;
+        model.base_rate as u64 + model.slope1 as u64 + model.slope2 as u64 * excess_utilization / (10_000 - kink)
+    };
+    rate.min(u32::MAX as u64) as u32
+}
+
+// Supply oranı = borç oranı * kullanım * (1 - rezerv faktörü)
+pub fn supply_rate(borrow_rate: u32, utilization: u32, reserve_factor: u32) -> u32 {
+    (borrow_rate as u64 * utilization as u64 / 10_000 * 10_000u32.saturating_sub(reserve_factor) as u64 / 10_000) as u32
+}
@@ -0,0 +1,279 @@
+use soroban_sdk::{Address, Env, Map, Symbol, TryFromVal, Val, Vec};
+
+use crate::interest::ledger_to_timestamp;
+use crate::storage_types::{
+    DataKey, InterestRateModel, LegacyLendingPool, LegacyMarketPool, LegacyUserBorrow,
+    LegacyUserSupply, LendingPool, UserBorrow, UserSupply, INDEX_PRECISION, LENDING_BUMP_AMOUNT,
+    LENDING_LIFETIME_THRESHOLD,
+};
+
+// Lending piyasaları ödünç verilen SEP-41 tokenin adresiyle anahtarlanır.
+//
+// Çoklu piyasadan önceki sürümlerde tek bir havuz kontratın kendi tokenini ödünç
+// veriyordu. O havuz ve kullanıcı kayıtları kontratın kendi adresindeki piyasa olarak
+// okunur, ilk yazmada piyasa anahtarlarına taşınır. Daha eski sürümlerde bu kayıtlar
+// instance storage'da ve faiz indeksleri olmadan tutuluyordu. Havuz çevrildiğinde
+// indeksler INDEX_PRECISION'dan başladığı için eski bakiyeler doğrudan ölçekli bakiye
+// olarak kullanılabilir. Eski havuzun son güncelleme ledger'ı zaman damgasına çevrilir.
+
+fn is_native(e: &Env, asset: &Address) -> bool {
+    *asset == e.current_contract_address()
+}
+
+pub fn read_markets(e: &Env) -> Vec<Address> {
+    let key = DataKey::Markets;
+    e.storage().instance().get(&key).unwrap_or(Vec::new(e))
+}
+
+pub fn has_market(e: &Env, asset: &Address) -> bool {
+    let key = DataKey::Market(asset.clone());
+    e.storage().persistent().has(&key) || (is_native(e, asset) && has_legacy_pool(e))
+}
+
+pub fn read_market(e: &Env, asset: &Address) -> Option<LendingPool> {
+    let key = DataKey::Market(asset.clone());
+    if let Some(pool) = e.storage().persistent().get::<DataKey, LendingPool>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+        return Some(pool);
+    }
+    if is_native(e, asset) {
+        read_legacy_pool(e)
+    } else {
+        None
+    }
+}
+
+// Kayıtta olmayan bir piyasa ilk yazıldığında piyasa listesine eklenir
+pub fn write_market(e: &Env, asset: &Address, pool: &LendingPool) {
+    let key = DataKey::Market(asset.clone());
+    if !e.storage().persistent().has(&key) {
+        let mut markets = read_markets(e);
+        markets.push_back(asset.clone());
+        e.storage().instance().set(&DataKey::Markets, &markets);
+    }
+    e.storage().persistent().set(&key, pool);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+    if is_native(e, asset) {
+        remove_legacy(e, &DataKey::LendingPool);
+    }
+}
+
+pub fn read_user_supply(e: &Env, asset: &Address, user: &Address) -> Option<UserSupply> {
+    let key = DataKey::MarketSupply(asset.clone(), user.clone());
+    if let Some(supply) = e.storage().persistent().get::<DataKey, UserSupply>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+        return Some(supply);
+    }
+    if !is_native(e, asset) {
+        return None;
+    }
+    let legacy_key = DataKey::UserSupply(user.clone());
+    if let Some(supply) = e.storage().persistent().get::<DataKey, UserSupply>(&legacy_key) {
+        return Some(supply);
+    }
+    let legacy: LegacyUserSupply = e.storage().instance().get(&legacy_key)?;
+    Some(UserSupply {
+        scaled_balance: legacy.amount + legacy.accrued_interest,
+    })
+}
+
+pub fn write_user_supply(e: &Env, asset: &Address, user: &Address, supply: &UserSupply) {
+    let key = DataKey::MarketSupply(asset.clone(), user.clone());
+    e.storage().persistent().set(&key, supply);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+    if is_native(e, asset) {
+        remove_legacy(e, &DataKey::UserSupply(user.clone()));
+    }
+}
+
+pub fn remove_user_supply(e: &Env, asset: &Address, user: &Address) {
+    let key = DataKey::MarketSupply(asset.clone(), user.clone());
+    e.storage().persistent().remove(&key);
+    if is_native(e, asset) {
+        remove_legacy(e, &DataKey::UserSupply(user.clone()));
+    }
+}
+
+pub fn read_user_borrow(e: &Env, asset: &Address, user: &Address) -> Option<UserBorrow> {
+    let key = DataKey::MarketBorrow(asset.clone(), user.clone());
+    if let Some(borrow) = e.storage().persistent().get::<DataKey, UserBorrow>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+        return Some(borrow);
+    }
+    if !is_native(e, asset) {
+        return None;
+    }
+    let legacy_key = DataKey::UserBorrow(user.clone());
+    if let Some(borrow) = e.storage().persistent().get::<DataKey, UserBorrow>(&legacy_key) {
+        return Some(borrow);
+    }
+    let legacy: LegacyUserBorrow = e.storage().instance().get(&legacy_key)?;
+    Some(UserBorrow {
+        scaled_debt: legacy.amount + legacy.accrued_interest,
+        collateral_deposited: legacy.collateral_deposited,
+    })
+}
+
+pub fn write_user_borrow(e: &Env, asset: &Address, user: &Address, borrow: &UserBorrow) {
+    let key = DataKey::MarketBorrow(asset.clone(), user.clone());
+    e.storage().persistent().set(&key, borrow);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+    if is_native(e, asset) {
+        remove_legacy(e, &DataKey::UserBorrow(user.clone()));
+    }
+}
+
+pub fn remove_user_borrow(e: &Env, asset: &Address, user: &Address) {
+    let key = DataKey::MarketBorrow(asset.clone(), user.clone());
+    e.storage().persistent().remove(&key);
+    if is_native(e, asset) {
+        remove_legacy(e, &DataKey::UserBorrow(user.clone()));
+    }
+}
+
+// Kullanıcının bir piyasadaki supply pozisyonunu teminat olarak kullanıp kullanmadığı
+pub fn read_use_as_collateral(e: &Env, asset: &Address, user: &Address) -> bool {
+    let key = DataKey::UseAsCollateral(asset.clone(), user.clone());
+    if let Some(enabled) = e.storage().persistent().get::<DataKey, bool>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+        enabled
+    } else {
+        false
+    }
+}
+
+pub fn write_use_as_collateral(e: &Env, asset: &Address, user: &Address, enabled: bool) {
+    let key = DataKey::UseAsCollateral(asset.clone(), user.clone());
+    if enabled {
+        e.storage().persistent().set(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LENDING_LIFETIME_THRESHOLD, LENDING_BUMP_AMOUNT);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+// Faiz modeli piyasa bazlı bir ayar olduğu için instance storage'da tutulur
+pub fn read_interest_rate_model(e: &Env, asset: &Address) -> Option<InterestRateModel> {
+    let key = DataKey::InterestRateModel(asset.clone());
+    e.storage().instance().get(&key)
+}
+
+pub fn write_interest_rate_model(e: &Env, asset: &Address, model: &InterestRateModel) {
+    let key = DataKey::InterestRateModel(asset.clone());
+    e.storage().instance().set(&key, model);
+}
+
+// Tüm piyasalarda borç pozisyonlarına teminat olarak yatırılan kendi token toplamı.
+// Acil çekimler bu miktara dokunmaz.
+pub fn read_total_collateral(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::TotalCollateral).unwrap_or(0)
+}
+
+pub fn write_total_collateral(e: &Env, amount: i128) {
+    e.storage().instance().set(&DataKey::TotalCollateral, &amount.max(0));
+}
+
+// Acil çekimle nakdi alınan piyasada supply, çekim ve borç kapanır. Kayıt o ana
+// kadar alınan toplamı tutar ki sonraki geri ödemeler ayrıca çekilebilsin.
+pub fn read_market_swept(e: &Env, asset: &Address) -> Option<i128> {
+    e.storage().instance().get(&DataKey::MarketSwept(asset.clone()))
+}
+
+pub fn write_market_swept(e: &Env, asset: &Address, amount: i128) {
+    e.storage().instance().set(&DataKey::MarketSwept(asset.clone()), &amount);
+}
+
+// Tek havuzlu sürümün havuzunu kontratın kendi tokeninin piyasasına taşır
+pub fn migrate_legacy_pool_to_market(e: &Env) {
+    if let Some(pool) = read_legacy_pool(e) {
+        write_market(e, &e.current_contract_address(), &pool);
+    }
+}
+
+fn has_legacy_pool(e: &Env) -> bool {
+    let key = DataKey::LendingPool;
+    e.storage().persistent().has(&key) || e.storage().instance().has(&key)
+}
+
+fn read_legacy_pool(e: &Env) -> Option<LendingPool> {
+    let key = DataKey::LendingPool;
+    if let Some(pool) = e.storage().persistent().get::<DataKey, LegacyMarketPool>(&key) {
+        return Some(from_legacy_market_pool(pool));
+    }
+    let legacy: LegacyLendingPool = e.storage().instance().get(&key)?;
+    Some(LendingPool {
+        total_supplied: legacy.total_supplied,
+        total_borrowed: legacy.total_borrowed,
+        supply_rate: legacy.supply_rate,
+        borrow_rate: legacy.borrow_rate,
+        utilization_rate: legacy.utilization_rate,
+        reserve_factor: legacy.reserve_factor,
+        last_update_timestamp: ledger_to_timestamp(
+            e.ledger().timestamp(),
+            e.ledger().sequence(),
+            legacy.last_update_ledger,
+        ),
+        collateral_factor: legacy.collateral_factor,
+        supply_index: INDEX_PRECISION,
+        borrow_index: INDEX_PRECISION,
+        scaled_total_supplied: legacy.total_supplied,
+        scaled_total_borrowed: legacy.total_borrowed,
+        total_reserves: 0,
+    })
+}
+
+// Rezerv takibinden önce açılan piyasalar rezervsiz başlar; o zamana kadar ayrılan
+// pay zaten havuz toplamlarının dışında kaldığı için kullanıcı bakiyeleri değişmez.
+pub fn migrate_market_reserves(e: &Env) {
+    for asset in read_markets(e).iter() {
+        let key = DataKey::Market(asset.clone());
+        let Some(fields) = e.storage().persistent().get::<DataKey, Map<Symbol, Val>>(&key) else {
+            continue;
+        };
+        if fields.contains_key(Symbol::new(e, "total_reserves")) {
+            continue;
+        }
+        if let Ok(pool) = LegacyMarketPool::try_from_val(e, &fields.to_val()) {
+            write_market(e, &asset, &from_legacy_market_pool(pool));
+        }
+    }
+}
+
+fn from_legacy_market_pool(pool: LegacyMarketPool) -> LendingPool {
+    LendingPool {
+        total_supplied: pool.total_supplied,
+        total_borrowed: pool.total_borrowed,
+        supply_rate: pool.supply_rate,
+        borrow_rate: pool.borrow_rate,
+        utilization_rate: pool.utilization_rate,
+        reserve_factor: pool.reserve_factor,
+        last_update_timestamp: pool.last_update_timestamp,
+        collateral_factor: pool.collateral_factor,
+        supply_index: pool.supply_index,
+        borrow_index: pool.borrow_index,
+        scaled_total_supplied: pool.scaled_total_supplied,
+        scaled_total_borrowed: pool.scaled_total_borrowed,
+        total_reserves: 0,
+    }
+}
+
+fn remove_legacy(e: &Env, key: &DataKey) {
+    e.storage().persistent().remove(key);
+    e.storage().instance().remove(key);
+}
@@ -0,0 +1,46 @@
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::storage_types::{
+    DataKey, MintLimit, MintRecord, MINT_HISTORY_BUMP_AMOUNT, MINT_HISTORY_LIFETIME_THRESHOLD,
+};
+
+pub fn read_mint_limit(e: &Env, minter: &Address) -> Option<MintLimit> {
+    let key = DataKey::MintLimit(minter.clone());
+    e.storage().instance().get(&key)
+}
+
+pub fn write_mint_limit(e: &Env, minter: &Address, limit: &Option<MintLimit>) {
+    let key = DataKey::MintLimit(minter.clone());
+    match limit {
+        Some(limit) => e.storage().instance().set(&key, limit),
+        None => {
+            e.storage().instance().remove(&key);
+            write_mint_history(e, minter, &Vec::new(e));
+        }
+    }
+}
+
+pub fn read_mint_history(e: &Env, minter: &Address) -> Vec<MintRecord> {
+    let key = DataKey::MintHistory(minter.clone());
+    if let Some(history) = e.storage().persistent().get::<DataKey, Vec<MintRecord>>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, MINT_HISTORY_LIFETIME_THRESHOLD, MINT_HISTORY_BUMP_AMOUNT);
+        return history;
+    }
+    // Eski sürümlerde geçmiş instance storage'daydı, ilk yazmada taşınır
+    e.storage().instance().get(&key).unwrap_or(Vec::new(e))
+}
+
+pub fn write_mint_history(e: &Env, minter: &Address, history: &Vec<MintRecord>) {
+    let key = DataKey::MintHistory(minter.clone());
+    e.storage().instance().remove(&key);
+    if history.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, history);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, MINT_HISTORY_LIFETIME_THRESHOLD, MINT_HISTORY_BUMP_AMOUNT);
+    }
+}
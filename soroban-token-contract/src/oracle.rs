@@ -0,0 +1,39 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol, Vec};
+
+use crate::storage_types::{DataKey, OracleConfig};
+
+// SEP-40 fiyat oracle arayüzünün kullanılan kısmı. Fiyatlar oracle'ın kote para
+// birimi cinsinden, tokenin bir tam birimi için ve oracle'ın ondalık hassasiyetiyle verilir.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+// Trait sadece oracle istemcisini üretmek için tanımlanır
+#[allow(dead_code)]
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    // Varlığın en son fiyatı
+    fn lastprice(e: Env, asset: Asset) -> Option<PriceData>;
+    // Varlığın en yeniden eskiye son `records` fiyatı
+    fn prices(e: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>>;
+}
+
+pub fn read_oracle_config(e: &Env) -> Option<OracleConfig> {
+    let key = DataKey::OracleConfig;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_oracle_config(e: &Env, config: &OracleConfig) {
+    let key = DataKey::OracleConfig;
+    e.storage().instance().set(&key, config);
+}
@@ -0,0 +1,17 @@
+use soroban_sdk::Env;
+
+use crate::storage_types::{DataKey, PauseOperation};
+
+pub fn read_paused(e: &Env, operation: PauseOperation) -> bool {
+    let key = DataKey::Paused(operation);
+    e.storage().instance().get(&key).unwrap_or(false)
+}
+
+pub fn write_paused(e: &Env, operation: PauseOperation, paused: bool) {
+    let key = DataKey::Paused(operation);
+    if paused {
+        e.storage().instance().set(&key, &true);
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
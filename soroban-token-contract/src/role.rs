@@ -0,0 +1,39 @@
+use soroban_sdk::{Address, Env};
+
+use crate::storage_types::{DataKey, Role};
+
+pub const ALL_ROLES: [Role; 8] = [
+    Role::Minter,
+    Role::Freezer,
+    Role::VestingManager,
+    Role::StakingManager,
+    Role::RiskManager,
+    Role::Pauser,
+    Role::Treasurer,
+    Role::Compliance,
+];
+
+pub fn has_role(e: &Env, role: Role, account: &Address) -> bool {
+    let key = DataKey::Role(role, account.clone());
+    e.storage().instance().has(&key)
+}
+
+pub fn write_role(e: &Env, role: Role, account: &Address) {
+    let key = DataKey::Role(role, account.clone());
+    e.storage().instance().set(&key, &true);
+}
+
+pub fn remove_role(e: &Env, role: Role, account: &Address) {
+    let key = DataKey::Role(role, account.clone());
+    e.storage().instance().remove(&key);
+}
+
+// Eski hesabın sahip olduğu rolleri yeni hesaba devret
+pub fn transfer_roles(e: &Env, from: &Address, to: &Address) {
+    for role in ALL_ROLES {
+        if has_role(e, role, from) {
+            remove_role(e, role, from);
+            write_role(e, role, to);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use soroban_sdk::Env;
+
+use crate::storage_types::DataKey;
+
+pub fn read_schema_version(e: &Env) -> u32 {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_schema_version(e: &Env, version: u32) {
+    let key = DataKey::SchemaVersion;
+    e.storage().instance().set(&key, &version);
+}
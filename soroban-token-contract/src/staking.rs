@@ -0,0 +1,31 @@
+use crate::storage_types::{DataKey, StakeInfo, STAKE_BUMP_AMOUNT, STAKE_LIFETIME_THRESHOLD};
+use soroban_sdk::{Address, Env};
+
+pub fn read_stake_info(e: &Env, user: &Address) -> Option<StakeInfo> {
+    let key = DataKey::StakeInfo(user.clone());
+    let stake_info = e.storage().persistent().get::<DataKey, StakeInfo>(&key);
+    if stake_info.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, STAKE_LIFETIME_THRESHOLD, STAKE_BUMP_AMOUNT);
+    }
+    stake_info
+}
+
+pub fn has_stake_info(e: &Env, user: &Address) -> bool {
+    let key = DataKey::StakeInfo(user.clone());
+    e.storage().persistent().has(&key)
+}
+
+pub fn write_stake_info(e: &Env, user: &Address, stake_info: &StakeInfo) {
+    let key = DataKey::StakeInfo(user.clone());
+    e.storage().persistent().set(&key, stake_info);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, STAKE_LIFETIME_THRESHOLD, STAKE_BUMP_AMOUNT);
+}
+
+pub fn remove_stake_info(e: &Env, user: &Address) {
+    let key = DataKey::StakeInfo(user.clone());
+    e.storage().persistent().remove(&key);
+}
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN, Vec};
 
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
@@ -6,8 +6,35 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 
 pub(crate) const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const STAKE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const STAKE_LIFETIME_THRESHOLD: u32 = STAKE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// Hesap bazlı kayıtlar persistent storage'da tutulur ve bakiyeler gibi her okuma
+// ve yazmada TTL'leri uzatılır. Instance storage sadece kontrat geneli ayarlar içindir.
+pub(crate) const VESTING_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const VESTING_LIFETIME_THRESHOLD: u32 = VESTING_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const FREEZE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const FREEZE_LIFETIME_THRESHOLD: u32 = FREEZE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const LENDING_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const LENDING_LIFETIME_THRESHOLD: u32 = LENDING_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const MINT_HISTORY_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const MINT_HISTORY_LIFETIME_THRESHOLD: u32 = MINT_HISTORY_BUMP_AMOUNT - DAY_IN_LEDGERS;
+// Mint sınırı penceresinin bölündüğü kova sayısı. Geçmiş en fazla bu kadar (+1) kayıt tutar.
+pub(crate) const MINT_WINDOW_BUCKETS: u32 = 24;
 // Kontrat sabitlerini tanımlama
-pub(crate) const REWARD_PRECISION: i128 = 10000; // Ödül hesaplamaları için hassasiyet faktörü
+// Timelock ile sıraya alınan işlemlerin çalıştırılabilmesi için geçmesi gereken en az süre
+pub(crate) const TIMELOCK_DELAY: u32 = 2 * DAY_IN_LEDGERS;
+// Bu sürümün beklediği storage düzeni. Düzen değiştiğinde artırılır ve migrate()
+// içine ilgili adım eklenir. Sürüm kaydı olmayan eski kurulumlar 0 kabul edilir.
+pub(crate) const CURRENT_SCHEMA_VERSION: u32 = 8;
+pub(crate) const REWARD_PRECISION: i128 = 1_000_000_000_000; // Token başına ödül hesaplamaları için hassasiyet faktörü
+pub(crate) const INDEX_PRECISION: i128 = 1_000_000_000_000;  // Lending faiz indekslerinin hassasiyet faktörü
+// Eski sürümden çevrilen staking havuzunun ödül dönemi (yeni fonlamalar bu süreye yayılır)
+pub(crate) const LEGACY_REWARD_DURATION: u32 = 7 * DAY_IN_LEDGERS;
 #[derive(Clone)]
 #[contracttype]
 pub struct AllowanceDataKey {
@@ -20,26 +47,112 @@ pub struct AllowanceValue {
     pub amount: i128,
     pub expiration_ledger: u32,
 }
+
+// Yeni admin tarafından kabul edilmeyi bekleyen admin devri
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub new_admin: Address,
+    pub expiration_ledger: Option<u32>, // Teklifin geçerli olduğu son ledger (None ise süresiz)
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct VestingSchedule {
+    pub id: u64,                    // Planın benzersiz kimliği
     pub beneficiary: Address,       // Vesting planından faydalanacak kişi
     pub total_amount: i128,         // Toplam hak edilecek token miktarı
     pub claimed_amount: i128,       // Şu ana kadar çekilmiş miktar
     pub start_ledger: u32,          // Vesting'in başlayacağı ledger
     pub cliff_ledger: u32,          // Cliff süresi (0 ise cliff yok)
     pub end_ledger: u32,            // Vesting'in biteceği ledger
+    pub period_ledgers: u32,        // Kademeli açılım adımı (0 ise lineer vesting)
+    pub initial_unlock_percent: u32, // Cliff anında açılan yüzde (0-100)
+    pub revocable: bool,            // Admin tarafından iptal edilebilir mi
+    pub funder: Option<Address>,    // Planı fonlayan hesap; iptalde hak edilmemiş kısım ona döner (None ise admin'e)
+}
+// Fonlayan kaydından önceki sıralı kimlikli vesting planı (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyIndexedVestingSchedule {
+    pub id: u64,
+    pub beneficiary: Address,
+    pub total_amount: i128,
+    pub claimed_amount: i128,
+    pub start_ledger: u32,
+    pub cliff_ledger: u32,
+    pub end_ledger: u32,
+    pub period_ledgers: u32,
+    pub initial_unlock_percent: u32,
+    pub revocable: bool,
 }
+// İlk sürümün faydalanıcı adresiyle anahtarlanan vesting planı (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyVestingSchedule {
+    pub beneficiary: Address,
+    pub total_amount: i128,
+    pub claimed_amount: i128,
+    pub start_ledger: u32,
+    pub cliff_ledger: u32,
+    pub end_ledger: u32,
+}
+// Yeni bir vesting planı oluşturmak için gereken parametreler
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct VestingParams {
+    pub beneficiary: Address,
+    pub amount: i128,
+    pub start_ledger: u32,
+    pub cliff_ledger: u32,
+    pub end_ledger: u32,
+    pub period_ledgers: u32,
+    pub initial_unlock_percent: u32,
+    pub revocable: bool,
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct StakeInfo {
-    pub amount: i128,           // Stake edilen token miktarı
-    pub since_ledger: u32,      // Stake edildiği ledger numarası
-    pub last_claim_ledger: u32, // Son ödül çekim ledger numarası
+    pub amount: i128,                     // Stake edilen token miktarı
+    pub since_ledger: u32,                // Stake edildiği ledger numarası
+    pub user_reward_per_token_paid: i128, // Son güncellemede kullanıcıya işlenen token başına ödül
+    pub rewards: i128,                    // Kazanılmış ama henüz çekilmemiş ödül
+}
+// Eski sürümün instance storage'daki stake haritasında kullanılan yapı (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyStakeInfo {
+    pub amount: i128,
+    pub since_ledger: u32,
+    pub last_claim_ledger: u32,
+}
+// Token başına ödül birikiminden önceki sürümün havuz yapısı (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyPoolInfo {
+    pub token_id: Address,
+    pub reward_token_id: Address,
+    pub reward_rate: u32,        // Ledger başına stake'in on binde biri cinsinden ödül
+    pub total_staked: i128,
+    pub min_stake_duration: u32,
 }
+// Eski havuz çevrildiğinde taşınmamış stake'lerin birikmiş ödülünü hesaplamak için
+// saklanan koşullar
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyRewardTerms {
+    pub reward_rate: u32,
+    pub end_ledger: u32,         // Eski modelde ödülün biriktiği son ledger
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct PoolInfo {
     pub token_id: Address,  
     pub reward_token_id: Address,    // Stake edilecek token adresi (bu, kontratın kendi tokeni olabilir)
-    pub reward_rate: u32,       // Ödül oranı (her 10000 ledger başına birim başına ödül)
+    pub reward_rate: i128,           // Ledger başına dağıtılan toplam ödül miktarı
+    pub reward_duration: u32,        // Her fonlamada ödülün dağıtılacağı süre (ledger)
+    pub period_finish: u32,          // Mevcut ödül döneminin bittiği ledger
+    pub last_update_ledger: u32,     // reward_per_token_stored'un son güncellendiği ledger
+    pub reward_per_token_stored: i128, // Stake edilen token başına birikmiş ödül (REWARD_PRECISION ile ölçekli)
     pub total_staked: i128,     // Toplam stake edilen miktar
     pub min_stake_duration: u32, // Minimum stake süresi (ledger sayısı cinsinden)
 }
@@ -47,34 +160,230 @@ pub struct PoolInfo {
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct LendingPool {
-    pub total_supplied: i128,        // Toplam yatırılan miktar
-    pub total_borrowed: i128,        // Toplam ödünç alınan miktar
+    pub total_supplied: i128,        // Toplam yatırılan miktar (faiz dahil, indeksten hesaplanır)
+    pub total_borrowed: i128,        // Toplam borç (faiz dahil, indeksten hesaplanır)
     pub supply_rate: u32,            // Yıllık faiz oranı (baz puan olarak)
     pub borrow_rate: u32,            // Ödünç alma faiz oranı
     pub utilization_rate: u32,       // Kullanım oranı (%)
     pub reserve_factor: u32,         // Rezerv faktörü (%)
-    pub last_update_ledger: u32,     // Son güncelleme ledger'ı
+    pub last_update_timestamp: u64,  // Faizin son işlendiği ledger zaman damgası (saniye)
     pub collateral_factor: u32,      // Teminat faktörü (%)
+    pub supply_index: i128,          // Bir ölçekli supply biriminin token karşılığı (INDEX_PRECISION ile ölçekli)
+    pub borrow_index: i128,          // Bir ölçekli borç biriminin token karşılığı (INDEX_PRECISION ile ölçekli)
+    pub scaled_total_supplied: i128, // Kullanıcıların ölçekli supply bakiyelerinin toplamı
+    pub scaled_total_borrowed: i128, // Kullanıcıların ölçekli borçlarının toplamı
+    pub total_reserves: i128,        // Borç faizinden rezerv faktörü kadar ayrılan, çekilmemiş protokol payı
+}
+
+// Rezerv takibinden önceki indeksli piyasa yapısı (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyMarketPool {
+    pub total_supplied: i128,
+    pub total_borrowed: i128,
+    pub supply_rate: u32,
+    pub borrow_rate: u32,
+    pub utilization_rate: u32,
+    pub reserve_factor: u32,
+    pub last_update_timestamp: u64,
+    pub collateral_factor: u32,
+    pub supply_index: i128,
+    pub borrow_index: i128,
+    pub scaled_total_supplied: i128,
+    pub scaled_total_borrowed: i128,
+}
+
+// Kullanıma bağlı faiz modeli (tümü baz puan). Borç oranı kullanım kink'e kadar
+// base_rate'ten base_rate + slope1'e, kink'ten %100'e kadar da slope2 kadar daha artar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct InterestRateModel {
+    pub base_rate: u32,              // Kullanım %0 iken borç oranı
+    pub slope1: u32,                 // Kink'e kadar eklenen oran
+    pub slope2: u32,                 // Kink'ten %100 kullanıma kadar eklenen oran
+    pub kink: u32,                   // Eğimin değiştiği kullanım oranı
 }
 
-// Kullanıcı Supply bilgisi
+// Teminat ve borçları ortak bir kote para biriminde değerlemek için kullanılan
+// SEP-40 fiyat oracle'ı ve fiyat kontrolleri
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct OracleConfig {
+    pub oracle: Address,
+    pub max_age: u64,                // Fiyatın kabul edildiği en fazla yaş (saniye)
+    pub max_deviation_bps: u32,      // Önceki tura göre izin verilen en fazla fiyat sapması
+}
+
+// Kullanıcı Supply bilgisi. Güncel bakiye = scaled_balance * supply_index / INDEX_PRECISION
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct UserSupply {
-    pub amount: i128,                // Yatırılan miktar
-    pub last_update_ledger: u32,     // Son güncelleme ledger'ı
-    pub accrued_interest: i128,      // Birikmiş faiz
+    pub scaled_balance: i128,
 }
 
-// Kullanıcı Borrow bilgisi
+// Kullanıcı Borrow bilgisi. Güncel borç = scaled_debt * borrow_index / INDEX_PRECISION
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub struct UserBorrow {
-    pub amount: i128,                // Ödünç alınan miktar
-    pub last_update_ledger: u32,     // Son güncelleme ledger'ı
-    pub accrued_interest: i128,      // Birikmiş faiz
+    pub scaled_debt: i128,
     pub collateral_deposited: i128,  // Yatırılan teminat
 }
+
+// İndekslerden önceki sürümün lending yapıları (sadece taşıma için)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyLendingPool {
+    pub total_supplied: i128,
+    pub total_borrowed: i128,
+    pub supply_rate: u32,
+    pub borrow_rate: u32,
+    pub utilization_rate: u32,
+    pub reserve_factor: u32,
+    pub last_update_ledger: u32,
+    pub collateral_factor: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyUserSupply {
+    pub amount: i128,
+    pub last_update_ledger: u32,
+    pub accrued_interest: i128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LegacyUserBorrow {
+    pub amount: i128,
+    pub last_update_ledger: u32,
+    pub accrued_interest: i128,
+    pub collateral_deposited: i128,
+}
+// Bir minter'ın kayan pencere içinde mint edebileceği en fazla miktar
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct MintLimit {
+    pub amount: i128,                // Pencere başına mint sınırı
+    pub window_ledgers: u32,         // Pencere uzunluğu (ledger)
+}
+
+// Pencerenin bir kovasında mint edilen toplam, süresi geçen kovalar her mint'te silinir
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct MintRecord {
+    pub ledger: u32,                 // Kovanın başladığı ledger
+    pub amount: i128,
+}
+// Hesabın hangi yöndeki token hareketlerinin engellendiği
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum FreezeMode {
+    Outgoing,                        // Gönderme, yakma ve teminat/supply yatırma engellenir
+    Incoming,                        // Token alma, mint ve çekimler engellenir
+    All,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct FreezeStatus {
+    pub mode: FreezeMode,
+    pub reason: u32,                 // Uyum ekibinin kullandığı sebep kodu
+    pub since_ledger: u32,           // Dondurmanın başladığı ledger
+}
+// Yetkili fonksiyonları çağırabilmek için gereken roller. Roller admin tarafından verilir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Role {
+    Minter,
+    Freezer,
+    VestingManager,
+    StakingManager,
+    RiskManager,
+    Pauser,
+    Treasurer,
+    Compliance,
+}
+
+// Timelock gecikmesinden sonra uygulanan hassas parametre değişiklikleri
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum TimelockOperation {
+    UpdateLendingRates(Address, u32, u32), // (piyasa, supply_rate, borrow_rate)
+    UpdateCollateralFactor(Address, u32),  // (piyasa, faktör)
+    UpdateLiquidationParams(u32, u32),   // (threshold, penalty)
+    UpdateRewardDuration(u32),
+    ProposeAdmin(Address, Option<u32>),  // (new_admin, expiration_ledger)
+    UpdateMaxSupply(Option<i128>),       // None ise arz sınırı kaldırılır
+    UpdateMintLimit(Address, MintLimit), // (minter, limit)
+    RemoveMintLimit(Address),            // Minter'ın sınırını kaldırır
+    UpdateInterestRateModel(Address, InterestRateModel), // (piyasa, model)
+    UpdateOracle(OracleConfig),
+    Upgrade(BytesN<32>),                 // Yüklenmiş yeni WASM'ın hash'i
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct QueuedOperation {
+    pub id: u64,
+    pub operation: TimelockOperation,
+    pub proposer: Address,               // İşlemi sıraya alan hesap
+    pub eta_ledger: u32,                 // İşlemin çalıştırılabileceği en erken ledger
+}
+
+// Admin konseyi: imzacılar ve bir teklifin uygulanması için gereken onay sayısı
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Council {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+// Konsey onayı ile uygulanan admin işlemleri
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum CouncilAction {
+    Mint(Address, i128),                   // (to, amount)
+    EmergencyWithdrawRewards(Address),     // Fonların gönderileceği adres
+    EmergencyWithdrawLendingPool(Address, Address), // (piyasa, fonların gönderileceği adres)
+    QueueOperation(TimelockOperation),     // Parametre güncellemeleri, yükseltme ve admin devri
+    UpdateCouncil(Vec<Address>, u32),      // (signers, threshold)
+    GrantRole(Role, Address),
+    RevokeRole(Role, Address),
+    CancelAdminProposal,
+    Migrate(Option<i128>),                 // Toplam arz kaydı olmayan kurulumlar için denetlenmiş arz
+    CancelOperation(u64),                  // Sıradaki timelock işleminin kimliği
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct CouncilProposal {
+    pub id: u64,
+    pub action: CouncilAction,
+    pub approvals: Vec<Address>,           // Teklifi onaylayan imzacılar
+}
+
+// Yöneticinin ayrı ayrı durdurabildiği işlem grupları
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum PauseOperation {
+    Transfer,
+    MintBurn,
+    Staking,
+    VestingClaim,
+    Supply,
+    Withdraw,
+    Borrow,
+    Repay,
+    Liquidate,
+}
+
+// İlk sürümün DataKey'inde aynı adla farklı tipte anahtar kullanan kayıtlar (sadece taşıma için)
+#[derive(Clone)]
+#[contracttype]
+pub enum LegacyDataKey {
+    VestingSchedule(Address),
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -83,11 +392,41 @@ pub enum DataKey {
     Nonce(Address),
     State(Address),
     Admin,
+    PendingAdmin,
     Frozen(Address), 
-    VestingSchedule(Address),
+    VestingSchedule(u64),
+    VestingIds(Address),
+    NextVestingId,
+    VestingEscrow,
     StakeInfo(Address),
     PoolInfo,
-    LendingPool,
-    UserSupply,
-    UserBorrow,
+    LegacyRewardTerms,
+    LendingPool,                         // Tek havuzlu sürümün havuzu (sadece taşıma için)
+    UserSupply(Address),                 // Tek havuzlu sürümün kayıtları (sadece taşıma için)
+    UserBorrow(Address),
+    LiquidationThreshold,
+    LiquidationPenalty,
+    Paused(PauseOperation),
+    Role(Role, Address),
+    TimelockQueue,
+    NextOperationId,
+    Council,
+    CouncilProposal(u64),
+    NextCouncilProposalId,
+    SchemaVersion,
+    TotalSupply,
+    MaxSupply,
+    MintLimit(Address),
+    MintHistory(Address),
+    ClawbackEnabled,
+    FreezeStatus(Address),
+    Markets,
+    Market(Address),                     // Ödünç verilen token -> LendingPool
+    MarketSupply(Address, Address),      // (token, kullanıcı)
+    MarketBorrow(Address, Address),      // (token, kullanıcı)
+    UseAsCollateral(Address, Address),   // (token, kullanıcı) supply teminat olarak sayılıyor mu
+    InterestRateModel(Address),
+    OracleConfig,
+    TotalCollateral,                     // Borç pozisyonlarına yatırılan kendi token toplamı
+    MarketSwept(Address),                // Acil çekimle piyasadan alınan toplam (varsa piyasa kapalıdır)
 }
\ No newline at end of file
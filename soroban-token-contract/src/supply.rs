@@ -0,0 +1,57 @@
+use soroban_sdk::{panic_with_error, Env};
+
+use crate::error::SupplyError;
+use crate::storage_types::DataKey;
+
+pub fn has_total_supply(e: &Env) -> bool {
+    let key = DataKey::TotalSupply;
+    e.storage().instance().has(&key)
+}
+
+pub fn read_total_supply(e: &Env) -> i128 {
+    let key = DataKey::TotalSupply;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+// Arz takibinden önce kurulan kontratlarda toplam arz, migrate denetlenmiş miktarı
+// yazana kadar bilinmez. Arzı değiştiren işlemler o zamana kadar reddedilir; aksi
+// halde yazılan kayıt migrate'in tohumlamasını atlatırdı.
+pub fn read_seeded_total_supply(e: &Env) -> i128 {
+    let key = DataKey::TotalSupply;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(e, SupplyError::TotalSupplyRequired))
+}
+
+pub fn write_total_supply(e: &Env, amount: i128) {
+    let key = DataKey::TotalSupply;
+    e.storage().instance().set(&key, &amount);
+}
+
+pub fn read_max_supply(e: &Env) -> Option<i128> {
+    let key = DataKey::MaxSupply;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_max_supply(e: &Env, max_supply: Option<i128>) {
+    let key = DataKey::MaxSupply;
+    match max_supply {
+        Some(max_supply) => e.storage().instance().set(&key, &max_supply),
+        None => e.storage().instance().remove(&key),
+    }
+}
+
+// Arz takibinden önce kurulan kontratlarda toplam arz kaydı yoktur. Kayıt yoksa
+// admin'in migrate'e verdiği denetlenmiş dolaşım miktarı yazılır.
+pub fn migrate_total_supply(e: &Env, total_supply: Option<i128>) {
+    if has_total_supply(e) {
+        return;
+    }
+    let total_supply =
+        total_supply.unwrap_or_else(|| panic_with_error!(e, SupplyError::TotalSupplyRequired));
+    if total_supply < 0 || read_max_supply(e).is_some_and(|max_supply| total_supply > max_supply) {
+        panic_with_error!(e, SupplyError::InvalidTotalSupply);
+    }
+    write_total_supply(e, total_supply);
+}
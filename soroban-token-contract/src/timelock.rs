@@ -0,0 +1,24 @@
+use soroban_sdk::{Env, Vec};
+
+use crate::storage_types::{DataKey, QueuedOperation};
+
+pub fn read_queued_operations(e: &Env) -> Vec<QueuedOperation> {
+    let key = DataKey::TimelockQueue;
+    e.storage().instance().get(&key).unwrap_or(Vec::new(e))
+}
+
+pub fn write_queued_operations(e: &Env, operations: &Vec<QueuedOperation>) {
+    let key = DataKey::TimelockQueue;
+    if operations.is_empty() {
+        e.storage().instance().remove(&key);
+    } else {
+        e.storage().instance().set(&key, operations);
+    }
+}
+
+pub fn next_operation_id(e: &Env) -> u64 {
+    let key = DataKey::NextOperationId;
+    let id: u64 = e.storage().instance().get(&key).unwrap_or(0);
+    e.storage().instance().set(&key, &(id + 1));
+    id
+}
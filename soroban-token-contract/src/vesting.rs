@@ -0,0 +1,183 @@
+use crate::balance::{read_balance, receive_balance, spend_balance};
+use crate::storage_types::{
+    DataKey, LegacyDataKey, LegacyIndexedVestingSchedule, LegacyVestingSchedule, VestingSchedule,
+    VESTING_BUMP_AMOUNT, VESTING_LIFETIME_THRESHOLD,
+};
+use soroban_sdk::{Address, Env, Map, Symbol, TryFromVal, Val, Vec};
+
+pub fn read_vesting_schedule(e: &Env, id: u64) -> Option<VestingSchedule> {
+    let key = DataKey::VestingSchedule(id);
+    let schedule = e.storage().persistent().get::<DataKey, VestingSchedule>(&key);
+    if schedule.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, VESTING_LIFETIME_THRESHOLD, VESTING_BUMP_AMOUNT);
+    }
+    schedule
+}
+
+pub fn write_vesting_schedule(e: &Env, schedule: &VestingSchedule) {
+    let key = DataKey::VestingSchedule(schedule.id);
+    e.storage().persistent().set(&key, schedule);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, VESTING_LIFETIME_THRESHOLD, VESTING_BUMP_AMOUNT);
+}
+
+pub fn read_vesting_ids(e: &Env, beneficiary: &Address) -> Vec<u64> {
+    let key = DataKey::VestingIds(beneficiary.clone());
+    match e.storage().persistent().get::<DataKey, Vec<u64>>(&key) {
+        Some(ids) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, VESTING_LIFETIME_THRESHOLD, VESTING_BUMP_AMOUNT);
+            ids
+        }
+        None => Vec::new(e),
+    }
+}
+
+fn write_vesting_ids(e: &Env, beneficiary: &Address, ids: &Vec<u64>) {
+    let key = DataKey::VestingIds(beneficiary.clone());
+    if ids.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, ids);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, VESTING_LIFETIME_THRESHOLD, VESTING_BUMP_AMOUNT);
+    }
+}
+
+// Yeni bir plan kaydeder ve faydalanıcının plan listesine ekler
+pub fn add_vesting_schedule(e: &Env, schedule: &VestingSchedule) {
+    write_vesting_schedule(e, schedule);
+    let mut ids = read_vesting_ids(e, &schedule.beneficiary);
+    ids.push_back(schedule.id);
+    write_vesting_ids(e, &schedule.beneficiary, &ids);
+}
+
+pub fn remove_vesting_schedule(e: &Env, schedule: &VestingSchedule) {
+    let key = DataKey::VestingSchedule(schedule.id);
+    e.storage().persistent().remove(&key);
+
+    let mut ids = read_vesting_ids(e, &schedule.beneficiary);
+    if let Some(index) = ids.first_index_of(schedule.id) {
+        ids.remove(index);
+    }
+    write_vesting_ids(e, &schedule.beneficiary, &ids);
+}
+
+pub fn next_vesting_id(e: &Env) -> u64 {
+    let key = DataKey::NextVestingId;
+    let id: u64 = e.storage().instance().get(&key).unwrap_or(0);
+    e.storage().instance().set(&key, &(id + 1));
+    id
+}
+
+// Eski sürümde instance storage'da tutulan planları ve plan listelerini persistent
+// storage'a taşır. Planlar sıralı kimlikle saklandığı için hepsi bulunabilir.
+pub fn migrate_vesting_to_persistent(e: &Env) -> u32 {
+    let next_id: u64 = e.storage().instance().get(&DataKey::NextVestingId).unwrap_or(0);
+    let mut migrated = 0;
+    for id in 0..next_id {
+        let key = DataKey::VestingSchedule(id);
+        if let Some(legacy) = e.storage().instance().get::<_, LegacyIndexedVestingSchedule>(&key) {
+            e.storage().instance().remove(&key);
+            let schedule = from_legacy_indexed_schedule(legacy);
+            write_vesting_schedule(e, &schedule);
+
+            let ids_key = DataKey::VestingIds(schedule.beneficiary.clone());
+            if let Some(ids) = e.storage().instance().get::<_, Vec<u64>>(&ids_key) {
+                e.storage().instance().remove(&ids_key);
+                write_vesting_ids(e, &schedule.beneficiary, &ids);
+            }
+            migrated += 1;
+        }
+    }
+    migrated
+}
+
+// Fonlayan kaydından önce oluşturulan planlar fonlayansız taşınır; iptal edildiklerinde
+// hak edilmemiş kısım admin'e döner.
+pub fn migrate_vesting_funders(e: &Env) {
+    let next_id: u64 = e.storage().instance().get(&DataKey::NextVestingId).unwrap_or(0);
+    for id in 0..next_id {
+        let key = DataKey::VestingSchedule(id);
+        let Some(fields) = e.storage().persistent().get::<DataKey, Map<Symbol, Val>>(&key) else {
+            continue;
+        };
+        if fields.contains_key(Symbol::new(e, "funder")) {
+            continue;
+        }
+        if let Ok(legacy) = LegacyIndexedVestingSchedule::try_from_val(e, &fields.to_val()) {
+            write_vesting_schedule(e, &from_legacy_indexed_schedule(legacy));
+        }
+    }
+}
+
+fn from_legacy_indexed_schedule(legacy: LegacyIndexedVestingSchedule) -> VestingSchedule {
+    VestingSchedule {
+        id: legacy.id,
+        beneficiary: legacy.beneficiary,
+        total_amount: legacy.total_amount,
+        claimed_amount: legacy.claimed_amount,
+        start_ledger: legacy.start_ledger,
+        cliff_ledger: legacy.cliff_ledger,
+        end_ledger: legacy.end_ledger,
+        period_ledgers: legacy.period_ledgers,
+        initial_unlock_percent: legacy.initial_unlock_percent,
+        revocable: legacy.revocable,
+        funder: None,
+    }
+}
+
+// İlk sürümde faydalanıcının tek planı adresiyle anahtarlanır, tokenler doğrudan
+// faydalanıcıya verilip hesabı dondurulurdu. Plan sıralı kimlikli yapıya çevrilir,
+// çekilmemiş tokenler faydalanıcının bakiyesinden emanete alınır ve dondurma kaldırılır.
+// Tokenler admin tarafından faydalanıcıya mint edildiği için fonlayan kaydı yoktur,
+// iptalde hak edilmemiş kısım admin'e döner. Eski plan yoksa false döner.
+pub fn migrate_legacy_vesting_schedule(e: &Env, beneficiary: &Address) -> bool {
+    let key = LegacyDataKey::VestingSchedule(beneficiary.clone());
+    let Some(legacy) = e.storage().instance().get::<_, LegacyVestingSchedule>(&key) else {
+        return false;
+    };
+    e.storage().instance().remove(&key);
+    e.storage().instance().remove(&DataKey::Frozen(beneficiary.clone()));
+
+    // Dondurma dışında harcanmış bakiye emanete alınamaz, plan kalan kısımla devam eder
+    let unclaimed = (legacy.total_amount - legacy.claimed_amount)
+        .min(read_balance(e, beneficiary.clone()))
+        .max(0);
+    if unclaimed == 0 {
+        return true;
+    }
+    spend_balance(e, beneficiary.clone(), unclaimed);
+    receive_balance(e, e.current_contract_address(), unclaimed);
+    write_vesting_escrow(e, read_vesting_escrow(e) + unclaimed);
+
+    let schedule = VestingSchedule {
+        id: next_vesting_id(e),
+        beneficiary: beneficiary.clone(),
+        total_amount: legacy.claimed_amount + unclaimed,
+        claimed_amount: legacy.claimed_amount,
+        start_ledger: legacy.start_ledger,
+        cliff_ledger: legacy.cliff_ledger,
+        end_ledger: legacy.end_ledger,
+        period_ledgers: 0,
+        initial_unlock_percent: 0,
+        revocable: true,
+        funder: None,
+    };
+    add_vesting_schedule(e, &schedule);
+    true
+}
+
+// Vesting planları için kontratta emanet (escrow) tutulan toplam token miktarı
+pub fn read_vesting_escrow(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::VestingEscrow).unwrap_or(0)
+}
+
+pub fn write_vesting_escrow(e: &Env, amount: i128) {
+    e.storage().instance().set(&DataKey::VestingEscrow, &amount);
+}
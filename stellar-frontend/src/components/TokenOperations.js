@@ -4,7 +4,8 @@ import { useSorobanReact } from '@soroban-react/core';
 import {
     Contract,
     nativeToScVal,
-    Address
+    Address,
+    xdr
 } from '@stellar/stellar-sdk';
 import ConnectWallet from './ConnectWallet';
 import TransactionHistory from './TransactionHistory';
@@ -53,6 +54,12 @@ const FreezeIcon = () => (
     </svg>
 );
 
+// Kontrattaki DAY_IN_LEDGERS ile aynı (yaklaşık 5 saniyelik ledger'lar)
+const DAY_IN_LEDGERS = 17280;
+
+// FreezeMode gibi veri taşımayan contracttype enum varyantları tek elemanlı vektör olarak kodlanır
+const enumToScVal = (variant) => xdr.ScVal.scvVec([nativeToScVal(variant, { type: 'symbol' })]);
+
 const WalletIcon = () => (
     <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="currentColor" className="wallet-icon">
         <path d="M2.273 5.625A4.483 4.483 0 015.25 4.5h13.5c1.141 0 2.183.425 2.977 1.125A3 3 0 0018.75 3H5.25a3 3 0 00-2.977 2.625zM2.273 8.625A4.483 4.483 0 015.25 7.5h13.5c1.141 0 2.183.425 2.977 1.125A3 3 0 0018.75 6H5.25a3 3 0 00-2.977 2.625zM5.25 9a3 3 0 00-3 3v6a3 3 0 003 3h13.5a3 3 0 003-3v-6a3 3 0 00-3-3H15a.75.75 0 00-.75.75 2.25 2.25 0 01-4.5 0A.75.75 0 009 9H5.25z" />
@@ -76,16 +83,18 @@ export default function TokenDashboard({ contractId }) {
 
     // Stake state
     const [stakeAmount, setStakeAmount] = useState('');
-    const [stakeDuration, setStakeDuration] = useState('30');
 
     // Vesting state
     const [vestingAmount, setVestingAmount] = useState('');
     const [vestingRecipient, setVestingRecipient] = useState('');
     const [vestingDuration, setVestingDuration] = useState('90');
     const [vestingCliff, setVestingCliff] = useState('30');
+    const [vestingRevocable, setVestingRevocable] = useState(false);
 
     // Freeze/Unfreeze state
     const [freezeAccountAddress, setFreezeAccountAddress] = useState('');
+    const [freezeMode, setFreezeMode] = useState('All');
+    const [freezeReason, setFreezeReason] = useState('0');
     const [unfreezeAccountAddress, setUnfreezeAccountAddress] = useState('');
 
 
@@ -167,7 +176,7 @@ export default function TokenDashboard({ contractId }) {
     // Stake process
     const handleStake = async (e) => {
         e.preventDefault();
-        if (!address || !stakeAmount || !stakeDuration || isSubmitting) return;
+        if (!address || !stakeAmount || isSubmitting) return;
         setIsSubmitting(true);
 
         try {
@@ -179,8 +188,7 @@ export default function TokenDashboard({ contractId }) {
                 {
                     args: [
                         new Address(address).toScVal(),
-                        nativeToScVal(parseInt(stakeAmount), { type: 'i128' }),
-                        nativeToScVal(parseInt(stakeDuration), { type: 'u32' })
+                        nativeToScVal(parseInt(stakeAmount), { type: 'i128' })
                     ],
                     fee: '1000000'
                 }
@@ -209,6 +217,12 @@ export default function TokenDashboard({ contractId }) {
         try {
             const contract = new Contract(contractId);
 
+            // Kontrat süreleri ledger numarası olarak alır; plan son ledger'dan başlatılır
+            const { sequence } = await server.getLatestLedger();
+            const startLedger = sequence;
+            const cliffLedger = startLedger + parseInt(vestingCliff) * DAY_IN_LEDGERS;
+            const endLedger = startLedger + parseInt(vestingDuration) * DAY_IN_LEDGERS;
+
             const result = await contract.call(
                 server,
                 'create_vesting',
@@ -217,8 +231,10 @@ export default function TokenDashboard({ contractId }) {
                         new Address(address).toScVal(),
                         new Address(vestingRecipient).toScVal(),
                         nativeToScVal(parseInt(vestingAmount), { type: 'i128' }),
-                        nativeToScVal(parseInt(vestingDuration), { type: 'u32' }),
-                        nativeToScVal(parseInt(vestingCliff), { type: 'u32' })
+                        nativeToScVal(startLedger, { type: 'u32' }),
+                        nativeToScVal(cliffLedger, { type: 'u32' }),
+                        nativeToScVal(endLedger, { type: 'u32' }),
+                        nativeToScVal(vestingRevocable, { type: 'bool' })
                     ],
                     fee: '1000000'
                 }
@@ -253,7 +269,10 @@ export default function TokenDashboard({ contractId }) {
                 'freeze_account',
                 {
                     args: [
-                        new Address(freezeAccountAddress).toScVal()
+                        new Address(address).toScVal(),
+                        new Address(freezeAccountAddress).toScVal(),
+                        enumToScVal(freezeMode),
+                        nativeToScVal(parseInt(freezeReason), { type: 'u32' })
                     ],
                     fee: '1000000'
                 }
@@ -286,6 +305,7 @@ export default function TokenDashboard({ contractId }) {
                 'unfreeze_account',
                 {
                     args: [
+                        new Address(address).toScVal(),
                         new Address(unfreezeAccountAddress).toScVal()
                     ],
                     fee: '1000000'
@@ -507,25 +527,9 @@ export default function TokenDashboard({ contractId }) {
                                         <p className="input-help">Amount of tokens you want to stake</p>
                                     </div>
 
-                                    <div className="form-group">
-                                        <label className="input-label">Staking Duration</label>
-                                        <select
-                                            className="select-field"
-                                            value={stakeDuration}
-                                            onChange={(e) => setStakeDuration(e.target.value)}
-                                            required
-                                        >
-                                            <option value="30">30 Days</option>
-                                            <option value="90">90 Days</option>
-                                            <option value="180">180 Days</option>
-                                            <option value="365">365 Days</option>
-                                        </select>
-                                        <p className="input-help">The duration for which tokens will remain locked</p>
-                                    </div>
-
                                     <div className="info-box success">
                                         <div className="info-box-title">Information</div>
-                                        <p>The tokens you stake are locked for the specified period. During this period, you will earn 8% annual returns.</p>
+                                        <p>The tokens you stake are locked for the pool's minimum staking duration and earn rewards while they are staked.</p>
                                     </div>
 
                                     <button
@@ -610,6 +614,18 @@ export default function TokenDashboard({ contractId }) {
                                         </div>
                                     </div>
 
+                                    <div className="form-group">
+                                        <label className="input-label">
+                                            <input
+                                                type="checkbox"
+                                                checked={vestingRevocable}
+                                                onChange={(e) => setVestingRevocable(e.target.checked)}
+                                            />{' '}
+                                            Revocable
+                                        </label>
+                                        <p className="input-help">A revocable plan can be cancelled; vested tokens go to the recipient and the rest back to you</p>
+                                    </div>
+
                                     <div className="info-box purple">
                                         <div className="info-box-title">Information</div>
                                         <p>The vesting plan provides for a gradual release of tokens over a specified period.
@@ -689,9 +705,38 @@ export default function TokenDashboard({ contractId }) {
                                                 <p className="input-help">Stellar address of the account you want to freeze</p>
                                             </div>
 
+                                            <div className="form-group">
+                                                <label className="input-label">Freeze Mode</label>
+                                                <select
+                                                    className="select-field"
+                                                    value={freezeMode}
+                                                    onChange={(e) => setFreezeMode(e.target.value)}
+                                                    required
+                                                >
+                                                    <option value="All">All transfers</option>
+                                                    <option value="Outgoing">Outgoing only</option>
+                                                    <option value="Incoming">Incoming only</option>
+                                                </select>
+                                                <p className="input-help">Direction of token movements to block</p>
+                                            </div>
+
+                                            <div className="form-group">
+                                                <label className="input-label">Reason Code</label>
+                                                <input
+                                                    type="number"
+                                                    placeholder="0"
+                                                    className="input-field"
+                                                    value={freezeReason}
+                                                    onChange={(e) => setFreezeReason(e.target.value)}
+                                                    min="0"
+                                                    required
+                                                />
+                                                <p className="input-help">Compliance reason code stored with the freeze</p>
+                                            </div>
+
                                             <div className="info-box warning">
                                                 <div className="info-box-title">Warning</div>
-                                                <p>Frozen accounts cannot perform transfers. Only the administrator can perform this operation.</p>
+                                                <p>Frozen accounts cannot move tokens in the selected direction. Only accounts with the Freezer role can perform this operation.</p>
                                             </div>
 
                                             <button